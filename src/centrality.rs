@@ -0,0 +1,215 @@
+use std::collections::VecDeque;
+
+use rand::{rngs::StdRng, seq::index::sample, SeedableRng};
+use rayon::prelude::*;
+
+use crate::graph::Csr;
+
+/// Shortest path counts and distances from `source` by BFS (unweighted).
+/// Returns `(order, sigma, dist, preds)` where `order` is the visiting order.
+#[allow(clippy::type_complexity)]
+fn bfs(csr: &Csr, source: usize) -> (Vec<usize>, Vec<f64>, Vec<usize>, Vec<Vec<usize>>) {
+    let n = csr.size();
+    let mut order = Vec::new();
+    let mut sigma = vec![0.; n];
+    let mut dist = vec![usize::MAX; n];
+    let mut preds: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut queue = VecDeque::new();
+
+    sigma[source] = 1.;
+    dist[source] = 0;
+    queue.push_back(source);
+    while let Some(v) = queue.pop_front() {
+        order.push(v);
+        for &w in csr.neighbors(v) {
+            if dist[w] == usize::MAX {
+                dist[w] = dist[v] + 1;
+                queue.push_back(w);
+            }
+            if dist[w] == dist[v] + 1 {
+                sigma[w] += sigma[v];
+                preds[w].push(v);
+            }
+        }
+    }
+
+    (order, sigma, dist, preds)
+}
+
+/// Betweenness centrality by Brandes' algorithm on unweighted shortest paths,
+/// normalized by (n - 1)(n - 2) as in networkx.
+///
+/// If `samples` is given and smaller than the number of nodes, only that many
+/// randomly chosen sources are used and the result is scaled by n / samples.
+pub fn betweenness(csr: &Csr, samples: Option<usize>, seed: u64) -> Vec<f64> {
+    let n = csr.size();
+    let sources: Vec<usize> = match samples {
+        Some(k) if k < n => {
+            let mut rng = StdRng::seed_from_u64(seed);
+            sample(&mut rng, n, k).into_vec()
+        }
+        _ => (0..n).collect(),
+    };
+
+    let mut bc = sources
+        .par_iter()
+        .map(|&s| {
+            let (order, sigma, _, preds) = bfs(csr, s);
+            let mut delta = vec![0.; n];
+            let mut bc = vec![0.; n];
+            for &w in order.iter().rev() {
+                for &v in preds[w].iter() {
+                    delta[v] += sigma[v] / sigma[w] * (1. + delta[w]);
+                }
+                if w != s {
+                    bc[w] += delta[w];
+                }
+            }
+            bc
+        })
+        .reduce(
+            || vec![0.; n],
+            |mut a, b| {
+                a.iter_mut().zip(b).for_each(|(x, y)| *x += y);
+                a
+            },
+        );
+
+    if n > 2 {
+        let scale = n as f64 / sources.len() as f64 / ((n - 1) * (n - 2)) as f64;
+        bc.iter_mut().for_each(|x| *x *= scale);
+    }
+    bc
+}
+
+/// Closeness centrality with the Wasserman-Faust correction for disconnected graphs.
+pub fn closeness(csr: &Csr) -> Vec<f64> {
+    let n = csr.size();
+    (0..n)
+        .into_par_iter()
+        .map(|s| {
+            let (order, _, dist, _) = bfs(csr, s);
+            let total: usize = order.iter().map(|&v| dist[v]).sum();
+            let reachable = (order.len() - 1) as f64;
+            if total == 0 || n < 2 {
+                return 0.;
+            }
+            reachable / total as f64 * reachable / (n - 1) as f64
+        })
+        .collect()
+}
+
+/// Eigenvector centrality by power iteration on (A + I), L2 normalized.
+pub fn eigenvector(csr: &Csr, max_iter: usize, tol: f64) -> Vec<f64> {
+    let n = csr.size();
+    let mut x = vec![1. / n as f64; n];
+
+    for _ in 0..max_iter {
+        let mut next: Vec<f64> = (0..n)
+            .into_par_iter()
+            .map(|v| {
+                x[v] + csr
+                    .neighbors(v)
+                    .iter()
+                    .zip(csr.weights(v))
+                    .map(|(&u, w)| x[u] * w)
+                    .sum::<f64>()
+            })
+            .collect();
+        let norm = next.iter().map(|v| v * v).sum::<f64>().sqrt();
+        if norm == 0. {
+            return next;
+        }
+        next.iter_mut().for_each(|v| *v /= norm);
+
+        let diff: f64 = next.iter().zip(x.iter()).map(|(a, b)| (a - b).abs()).sum();
+        x = next;
+        if diff < n as f64 * tol {
+            break;
+        }
+    }
+
+    x
+}
+
+/// Weighted PageRank. Rank of dangling nodes is spread uniformly.
+pub fn pagerank(csr: &Csr, damping: f64, max_iter: usize, tol: f64) -> Vec<f64> {
+    let n = csr.size();
+    let strength: Vec<f64> = (0..n).map(|v| csr.strength(v)).collect();
+    let mut x = vec![1. / n as f64; n];
+
+    for _ in 0..max_iter {
+        let dangling: f64 = (0..n).filter(|&v| strength[v] == 0.).map(|v| x[v]).sum();
+        let base = (1. - damping + damping * dangling) / n as f64;
+        let next: Vec<f64> = (0..n)
+            .into_par_iter()
+            .map(|v| {
+                base + damping
+                    * csr
+                        .neighbors(v)
+                        .iter()
+                        .zip(csr.weights(v))
+                        .map(|(&u, w)| x[u] * w / strength[u])
+                        .sum::<f64>()
+            })
+            .collect();
+
+        let diff: f64 = next.iter().zip(x.iter()).map(|(a, b)| (a - b).abs()).sum();
+        x = next;
+        if diff < n as f64 * tol {
+            break;
+        }
+    }
+
+    x
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::{Edge, Graph};
+    use approx::*;
+
+    // 0 - 1 - 2 - 3
+    fn path() -> Csr {
+        let nodes: Vec<String> = (0..4).map(|x| x.to_string()).collect();
+        let edges = vec![
+            Edge::new(0, 1, 0.9, 1.),
+            Edge::new(1, 2, 0.8, 1.),
+            Edge::new(2, 3, 0.7, 1.),
+        ];
+        Graph::from_edges(&nodes, &edges).to_csr()
+    }
+
+    #[test]
+    fn test_betweenness_1() {
+        let bc = betweenness(&path(), None, 0);
+        assert_abs_diff_eq!(bc[0], 0.);
+        assert_abs_diff_eq!(bc[1], 2. / 3.);
+        assert_abs_diff_eq!(bc[2], 2. / 3.);
+        assert_abs_diff_eq!(bc[3], 0.);
+
+        // sampling every node is exact
+        assert_eq!(betweenness(&path(), Some(4), 0), bc);
+    }
+
+    #[test]
+    fn test_closeness_1() {
+        let cc = closeness(&path());
+        assert_abs_diff_eq!(cc[0], 3. / 6.);
+        assert_abs_diff_eq!(cc[1], 3. / 4.);
+    }
+
+    #[test]
+    fn test_eigenvector_and_pagerank_1() {
+        let csr = path();
+        let ev = eigenvector(&csr, 1000, 1e-9);
+        assert!(ev[1] > ev[0]);
+        assert_abs_diff_eq!(ev[0], ev[3], epsilon = 1e-6);
+
+        let pr = pagerank(&csr, 0.85, 1000, 1e-12);
+        assert_abs_diff_eq!(pr.iter().sum::<f64>(), 1., epsilon = 1e-9);
+        assert!(pr[1] > pr[0]);
+        assert_abs_diff_eq!(pr[1], pr[2], epsilon = 1e-9);
+    }
+}
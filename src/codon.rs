@@ -2,86 +2,346 @@ use anyhow::Result;
 use ndarray::{Array2, ArrayBase};
 use ndarray_stats::*;
 use std::collections::BTreeMap;
+use structopt::clap::arg_enum;
 
+use crate::genetic_code::GeneticCode;
+use crate::math;
 use crate::rank;
 
-fn make_codon_map() -> BTreeMap<String, usize> {
-    let nuc = &['A', 'G', 'C', 'T'];
-    let stop_codons = &["TGA", "TAA", "TAG"];
-    let mut map: BTreeMap<String, usize> = BTreeMap::new();
-
-    for n1 in nuc.iter() {
-        for n2 in nuc.iter() {
-            for n3 in nuc.iter() {
-                let codon = format!("{}{}{}", n1, n2, n3);
-                if stop_codons.contains(&codon.as_ref()) {
-                    continue;
+arg_enum! {
+    #[derive(Debug)]
+    pub enum CodonProfile {
+        Counts,
+        RSCU,
+    }
+}
+
+fn make_codon_map(code: &GeneticCode) -> BTreeMap<String, usize> {
+    code.sense_codons().into_iter().map(|c| (c, 0)).collect()
+}
+
+/// Counts of the sense codons of `code` (in `make_codon_map` order) over every complete
+/// codon of `seq`. Stop codons, codons with ambiguous bases and a trailing partial codon are
+/// not counted.
+pub fn make_codon_vec(seq: &str, code: &GeneticCode) -> Vec<usize> {
+    let mut codon_map = make_codon_map(code);
+
+    let seq = seq.to_uppercase().replace('U', "T");
+
+    for codon in seq.as_bytes().chunks_exact(3) {
+        if let Some(i) = std::str::from_utf8(codon)
+            .ok()
+            .and_then(|c| codon_map.get_mut(c))
+        {
+            *i += 1
+        }
+    }
+
+    codon_map.into_values().collect()
+}
+
+/// How `validate_cds` treats coding sequences.
+#[derive(Debug, Clone)]
+pub struct CdsOptions {
+    /// sequences shorter than this (nt, after trimming) are skipped
+    pub min_length: usize,
+    /// skip sequences not starting with a start codon of `genetic_code`
+    pub require_start: bool,
+    /// translation table giving stop and start codons
+    pub genetic_code: GeneticCode,
+}
+
+impl Default for CdsOptions {
+    fn default() -> Self {
+        Self {
+            min_length: 30,
+            require_start: false,
+            genetic_code: GeneticCode::default(),
+        }
+    }
+}
+
+/// Check a coding sequence. Returns the sequence to count, trimmed of a trailing partial
+/// codon and the terminal stop codon, with notes on what was trimmed; or the reason to skip
+/// it (too short, no start codon or an in-frame stop codon).
+pub fn validate_cds(
+    seq: &str,
+    options: &CdsOptions,
+) -> std::result::Result<(String, Vec<String>), String> {
+    let mut seq = seq.trim().to_uppercase().replace('U', "T");
+    let mut notes = vec![];
+
+    let extra = seq.len() % 3;
+    if extra != 0 {
+        seq.truncate(seq.len() - extra);
+        notes.push(format!("length not a multiple of 3, trimmed {} nt", extra));
+    }
+    let code = &options.genetic_code;
+    if seq.len() >= 3 && code.is_terminal_stop(&seq.as_bytes()[seq.len() - 3..]) {
+        seq.truncate(seq.len() - 3);
+    }
+    if options.require_start && !(seq.len() >= 3 && code.is_start(&seq.as_bytes()[..3])) {
+        return Err("no start codon".to_string());
+    }
+    if let Some(i) = seq.as_bytes().chunks_exact(3).position(|c| code.is_stop(c)) {
+        return Err(format!("in-frame stop codon at codon {}", i + 1));
+    }
+    if seq.len() < options.min_length {
+        return Err(format!("shorter than {} nt", options.min_length));
+    }
+
+    let ambiguous = seq
+        .as_bytes()
+        .chunks_exact(3)
+        .filter(|c| c.iter().any(|b| !b"ACGT".contains(b)))
+        .count();
+    if ambiguous > 0 {
+        notes.push(format!("{} codons with ambiguous bases ignored", ambiguous));
+    }
+
+    Ok((seq, notes))
+}
+
+/// A sequence `filter_cds` skipped or changed.
+#[derive(Debug, Clone, Serialize)]
+pub struct CdsReport {
+    pub gene: String,
+    pub status: String,
+    pub length: usize,
+    pub reason: String,
+}
+
+/// Validate every sequence, keeping the usable ones. Returns the kept ids and sequences and
+/// a report of skipped and trimmed sequences.
+pub fn filter_cds(
+    index: &[String],
+    seqs: &[String],
+    options: &CdsOptions,
+) -> (Vec<String>, Vec<String>, Vec<CdsReport>) {
+    let mut kept_index = vec![];
+    let mut kept_seqs = vec![];
+    let mut report = vec![];
+    for (id, seq) in index.iter().zip(seqs.iter()) {
+        match validate_cds(seq, options) {
+            Ok((cds, notes)) => {
+                if !notes.is_empty() {
+                    report.push(CdsReport {
+                        gene: id.clone(),
+                        status: "trimmed".to_string(),
+                        length: seq.len(),
+                        reason: notes.join("; "),
+                    });
                 }
-                map.entry(codon).or_insert(0);
+                kept_index.push(id.clone());
+                kept_seqs.push(cds);
             }
+            Err(reason) => report.push(CdsReport {
+                gene: id.clone(),
+                status: "skipped".to_string(),
+                length: seq.len(),
+                reason,
+            }),
+        }
+    }
+    (kept_index, kept_seqs, report)
+}
+
+/// Positions (in `make_codon_vec` order) of the synonymous codons of every amino acid.
+fn synonymous_families(code: &GeneticCode) -> Vec<Vec<usize>> {
+    let mut families: BTreeMap<char, Vec<usize>> = BTreeMap::new();
+    for (i, codon) in code.sense_codons().iter().enumerate() {
+        if let Some(aa) = code.amino_acid(codon.as_bytes()) {
+            families.entry(aa).or_default().push(i);
         }
     }
+    families.into_values().collect()
+}
 
-    map
+/// Relative synonymous codon usage: the count of each codon over the count expected if all
+/// synonymous codons were used equally. Amino acids absent from the gene get 1.
+pub fn rscu(counts: &[usize], code: &GeneticCode) -> Vec<f64> {
+    let mut values = vec![1.; counts.len()];
+    for family in synonymous_families(code) {
+        let total: usize = family.iter().map(|&i| counts[i]).sum();
+        if total == 0 {
+            continue;
+        }
+        for &i in family.iter() {
+            values[i] = (counts[i] * family.len()) as f64 / total as f64;
+        }
+    }
+    values
 }
 
-fn make_codon_vec(seq: &str) -> Vec<usize> {
-    let mut codon_map = make_codon_map();
+/// Effective number of codons (Wright 1990), from 20 (one codon per amino acid) to the
+/// number of sense codons (uniform usage).
+///
+/// Homozygosities F are averaged over the amino acids of each degeneracy class; a class with
+/// no amino acid observed at least twice takes the mean F of its neighbouring classes (F3 =
+/// (F2 + F4) / 2 for isoleucine in the standard code). `None` if no class is observed.
+pub fn enc(counts: &[usize], code: &GeneticCode) -> Option<f64> {
+    // degeneracy -> (amino acids, F of the observed ones)
+    let mut classes: BTreeMap<usize, (usize, Vec<f64>)> = BTreeMap::new();
+    for family in synonymous_families(code) {
+        let class = classes.entry(family.len()).or_insert((0, vec![]));
+        class.0 += 1;
+        let n: usize = family.iter().map(|&i| counts[i]).sum();
+        if family.len() == 1 || n < 2 {
+            continue;
+        }
+        let n = n as f64;
+        let homozygosity: f64 = family.iter().map(|&i| (counts[i] as f64 / n).powi(2)).sum();
+        class.1.push((n * homozygosity - 1.) / (n - 1.));
+    }
+
+    let observed: BTreeMap<usize, f64> = classes
+        .iter()
+        .filter(|(&k, (_, f))| k > 1 && !f.is_empty())
+        .map(|(&k, (_, f))| (k, math::mean(f)))
+        .collect();
+    if observed.is_empty() {
+        return None;
+    }
+
+    let mut enc = 0.;
+    for (&k, (n_families, _)) in classes.iter() {
+        if k == 1 {
+            enc += *n_families as f64;
+            continue;
+        }
+        let f = match observed.get(&k) {
+            Some(f) => *f,
+            None => {
+                let neighbors: Vec<f64> = [k - 1, k + 1]
+                    .iter()
+                    .filter_map(|x| observed.get(x).cloned())
+                    .collect();
+                if neighbors.is_empty() {
+                    math::mean(&observed.values().cloned().collect::<Vec<f64>>())
+                } else {
+                    math::mean(&neighbors)
+                }
+            }
+        };
+        enc += *n_families as f64 / f;
+    }
+    Some(enc.min(code.sense_codons().len() as f64))
+}
 
-    let seq: Vec<char> = seq.to_uppercase().chars().collect();
+/// GC content at the third position of synonymous codons (Met, Trp and other single codon
+/// amino acids excluded). `None` if the gene has no such codon.
+pub fn gc3s(counts: &[usize], code: &GeneticCode) -> Option<f64> {
+    let codons = code.sense_codons();
+    let (mut gc, mut total) = (0, 0);
+    for family in synonymous_families(code).iter().filter(|f| f.len() > 1) {
+        for &i in family.iter() {
+            total += counts[i];
+            if codons[i].ends_with(['G', 'C']) {
+                gc += counts[i];
+            }
+        }
+    }
+    if total == 0 {
+        None
+    } else {
+        Some(gc as f64 / total as f64)
+    }
+}
 
-    for i in (0..seq.len() - 3).step_by(3) {
-        let codon: String = seq[i..i + 3].iter().collect();
-        match codon_map.get_mut(&codon) {
-            Some(i) => *i += 1,
-            None => continue,
+/// Relative adaptiveness w of every codon (Sharp and Li 1987): its count in the reference
+/// genes over the count of the most used synonymous codon. Unused codons count 0.5 so that
+/// w is never 0.
+pub fn relative_adaptiveness(reference_counts: &[usize], code: &GeneticCode) -> Vec<f64> {
+    let mut w = vec![1.; reference_counts.len()];
+    for family in synonymous_families(code) {
+        let count = |i: usize| f64::max(reference_counts[i] as f64, 0.5);
+        let max = family.iter().map(|&i| count(i)).fold(0., f64::max);
+        for &i in family.iter() {
+            w[i] = count(i) / max;
         }
     }
+    w
+}
 
-    codon_map.into_iter().map(|(_, x)| x).collect()
+/// Codon adaptation index: the geometric mean of `w` over the codons of the gene, single
+/// codon amino acids excluded. `None` if the gene has no such codon.
+pub fn cai(counts: &[usize], w: &[f64], code: &GeneticCode) -> Option<f64> {
+    let (mut log_sum, mut total) = (0., 0);
+    for family in synonymous_families(code).iter().filter(|f| f.len() > 1) {
+        for &i in family.iter() {
+            log_sum += counts[i] as f64 * w[i].ln();
+            total += counts[i];
+        }
+    }
+    if total == 0 {
+        None
+    } else {
+        Some((log_sum / total as f64).exp())
+    }
 }
 
-fn make_codon_arr(seqs: &[String]) -> Result<Array2<usize>> {
-    const CODON_SIZE: usize = 61;
+pub fn make_codon_arr(seqs: &[String], code: &GeneticCode) -> Result<Array2<usize>> {
+    let codon_size = code.sense_codons().len();
     let seq_len = seqs.len();
 
     let mut vec: Vec<usize> = vec![];
 
     for seq in seqs.iter() {
-        let codon_vec = make_codon_vec(seq);
+        let codon_vec = make_codon_vec(seq, code);
         vec.extend(codon_vec);
     }
 
-    Ok(ArrayBase::from_shape_vec((seq_len, CODON_SIZE), vec)?)
+    Ok(ArrayBase::from_shape_vec((seq_len, codon_size), vec)?)
 }
 
-fn make_codon_corr(seqs: &[String]) -> Result<Array2<f64>> {
-    let codon_arr = make_codon_arr(seqs)?;
-    Ok(codon_arr.mapv(|x| x as f64).pearson_correlation()?)
+fn make_codon_corr(
+    seqs: &[String],
+    code: &GeneticCode,
+    profile: &CodonProfile,
+) -> Result<Array2<f64>> {
+    let codon_arr = make_codon_arr(seqs, code)?;
+    let profile_arr = match profile {
+        CodonProfile::Counts => codon_arr.mapv(|x| x as f64),
+        CodonProfile::RSCU => {
+            let values: Vec<f64> = codon_arr
+                .outer_iter()
+                .flat_map(|counts| rscu(&counts.to_vec(), code))
+                .collect();
+            ArrayBase::from_shape_vec(codon_arr.raw_dim(), values)?
+        }
+    };
+    Ok(profile_arr.pearson_correlation()?)
 }
 
-pub fn make_codon_rank(seqs: &[String]) -> Result<Array2<usize>> {
-    let codon_corr = make_codon_corr(seqs)?;
+/// Rank matrix of gene similarity by correlation of codon usage `profile`. RSCU removes the
+/// gene length effect of raw counts.
+pub fn make_codon_rank(
+    seqs: &[String],
+    code: &GeneticCode,
+    profile: &CodonProfile,
+) -> Result<Array2<usize>> {
+    let codon_corr = make_codon_corr(seqs, code, profile)?;
     rank::construct_rank_matrix_multithreading(&codon_corr, seqs.len())
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use approx::*;
 
     #[test]
     fn test_make_codon_map() {
-        let codon_map = make_codon_map();
+        let codon_map = make_codon_map(&GeneticCode::default());
         // check size (4 * 4 * 4 - 3)
-        assert_eq!(codon_map.iter().count(), 61);
+        assert_eq!(codon_map.len(), 61);
     }
 
     #[test]
     fn test_make_codon_vec_1() {
         let seq = "ATGCAGCCCCAGTGA";
-        let codon_vec = make_codon_vec(seq);
+        let codon_vec = make_codon_vec(seq, &GeneticCode::default());
 
-        let mut codon_map = make_codon_map();
+        let mut codon_map = make_codon_map(&GeneticCode::default());
         codon_map.insert("ATG".to_string(), 1);
         codon_map.insert("CAG".to_string(), 2);
         codon_map.insert("CCC".to_string(), 1);
@@ -98,4 +358,100 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn test_make_codon_vec_2() {
+        // the last codon is counted, short sequences do not panic
+        let codon_map = make_codon_map(&GeneticCode::default());
+        let ccc = codon_map.keys().position(|k| k == "CCC").unwrap();
+        assert_eq!(make_codon_vec("ATGCCC", &GeneticCode::default())[ccc], 1);
+        assert_eq!(
+            make_codon_vec("AT", &GeneticCode::default())
+                .iter()
+                .sum::<usize>(),
+            0
+        );
+        assert_eq!(
+            make_codon_vec("atgNNNccc", &GeneticCode::default())
+                .iter()
+                .sum::<usize>(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_validate_cds_1() {
+        let options = CdsOptions {
+            min_length: 6,
+            require_start: true,
+            ..CdsOptions::default()
+        };
+        let (seq, notes) = validate_cds("ATGCCCTAAG", &options).unwrap();
+        assert_eq!(seq, "ATGCCC");
+        assert_eq!(notes.len(), 1);
+
+        assert!(validate_cds("CCCATGTAA", &options).is_err());
+        assert!(validate_cds("ATGTAACCC", &options).is_err());
+        assert!(validate_cds("ATGTAA", &options).is_err());
+        assert!(validate_cds("AT", &CdsOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_filter_cds_1() {
+        let index: Vec<String> = vec!["a".to_string(), "b".to_string()];
+        let seqs: Vec<String> = vec![
+            "ATGCCCAAAGGGTTTCCCAAAGGGTTTCCCTGA".to_string(),
+            "ATG".to_string(),
+        ];
+        let (kept, _, report) = filter_cds(&index, &seqs, &CdsOptions::default());
+        assert_eq!(kept, ["a"]);
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].status, "skipped");
+    }
+
+    #[test]
+    fn test_validate_cds_2() {
+        // TAA codes for glutamine in the ciliate code, TGA is the only stop
+        let options = CdsOptions {
+            min_length: 6,
+            require_start: true,
+            genetic_code: GeneticCode::from_ncbi(6).unwrap(),
+        };
+        let (seq, _) = validate_cds("ATGTAACCCTGA", &options).unwrap();
+        assert_eq!(seq, "ATGTAACCC");
+        assert!(validate_cds("ATGTGACCC", &options).is_err());
+
+        let code = &options.genetic_code;
+        assert_eq!(make_codon_vec(&seq, code).len(), 63);
+        assert_eq!(make_codon_vec(&seq, code).iter().sum::<usize>(), 3);
+    }
+
+    #[test]
+    fn test_codon_bias_1() {
+        let code = GeneticCode::default();
+        let codons = code.sense_codons();
+        let pos = |c: &str| codons.iter().position(|x| x == c).unwrap();
+
+        // only GCC for alanine, 10 times
+        let mut counts = vec![0; codons.len()];
+        counts[pos("GCC")] = 10;
+        let values = rscu(&counts, &code);
+        assert_abs_diff_eq!(values[pos("GCC")], 4.);
+        assert_abs_diff_eq!(values[pos("GCA")], 0.);
+        assert_abs_diff_eq!(values[pos("TTT")], 1.);
+        assert_abs_diff_eq!(gc3s(&counts, &code).unwrap(), 1.);
+        assert!(enc(&[0; 61], &code).is_none());
+
+        // one codon per amino acid gives 20, uniform usage all 61
+        let mut biased = vec![0; codons.len()];
+        for family in synonymous_families(&code) {
+            biased[family[0]] = 10;
+        }
+        assert_abs_diff_eq!(enc(&biased, &code).unwrap(), 20.);
+        assert_abs_diff_eq!(enc(&[100; 61], &code).unwrap(), 61.);
+
+        let w = relative_adaptiveness(&biased, &code);
+        assert_abs_diff_eq!(cai(&biased, &w, &code).unwrap(), 1.);
+        assert!(cai(&[1; 61], &w, &code).unwrap() < 1.);
+    }
 }
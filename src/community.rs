@@ -0,0 +1,446 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::graph::Csr;
+
+/// Weighted graph used while aggregating communities. Self loops are kept apart from `adj`
+/// and count twice in the strength of a node.
+#[derive(Debug, Clone)]
+struct Aggregate {
+    adj: Vec<Vec<(usize, f64)>>,
+    self_loop: Vec<f64>,
+    strength: Vec<f64>,
+    total: f64,
+}
+
+impl Aggregate {
+    fn from_csr(csr: &Csr) -> Self {
+        let adj = (0..csr.size())
+            .map(|v| {
+                csr.neighbors(v)
+                    .iter()
+                    .cloned()
+                    .zip(csr.weights(v).iter().cloned())
+                    .collect()
+            })
+            .collect();
+        Self::new(adj, vec![0.; csr.size()])
+    }
+
+    fn new(adj: Vec<Vec<(usize, f64)>>, self_loop: Vec<f64>) -> Self {
+        let strength: Vec<f64> = adj
+            .iter()
+            .zip(self_loop.iter())
+            .map(|(a, s)| a.iter().map(|x| x.1).sum::<f64>() + 2. * s)
+            .collect();
+        let total = strength.iter().sum();
+        Self {
+            adj,
+            self_loop,
+            strength,
+            total,
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.adj.len()
+    }
+
+    /// Collapse every community of `partition` (labels 0..k) into a single node.
+    fn aggregate(&self, partition: &[usize]) -> Self {
+        let k = partition.iter().max().map_or(0, |x| x + 1);
+        let mut maps: Vec<BTreeMap<usize, f64>> = vec![BTreeMap::new(); k];
+        let mut self_loop = vec![0.; k];
+
+        for v in 0..self.size() {
+            let cv = partition[v];
+            self_loop[cv] += self.self_loop[v];
+            for &(u, w) in self.adj[v].iter() {
+                let cu = partition[u];
+                if cu == cv {
+                    // internal edges are seen from both ends
+                    self_loop[cv] += w / 2.;
+                } else {
+                    *maps[cv].entry(cu).or_default() += w;
+                }
+            }
+        }
+
+        let adj = maps.into_iter().map(|m| m.into_iter().collect()).collect();
+        Self::new(adj, self_loop)
+    }
+
+    /// Weights from `v` to each neighbouring community, excluding self loops.
+    fn community_weights(&self, v: usize, partition: &[usize]) -> BTreeMap<usize, f64> {
+        let mut weights = BTreeMap::new();
+        for &(u, w) in self.adj[v].iter() {
+            *weights.entry(partition[u]).or_default() += w;
+        }
+        weights
+    }
+}
+
+/// Renumber labels to 0..k. Larger communities get smaller labels; ties are broken by
+/// the first node of each community.
+pub fn relabel_by_size(labels: &[usize]) -> Vec<usize> {
+    let mut first_and_size: HashMap<usize, (usize, usize)> = HashMap::new();
+    for (v, &l) in labels.iter().enumerate() {
+        first_and_size.entry(l).or_insert((v, 0)).1 += 1;
+    }
+    let mut order: Vec<(usize, usize, usize)> = first_and_size
+        .into_iter()
+        .map(|(l, (first, size))| (l, first, size))
+        .collect();
+    order.sort_by(|a, b| b.2.cmp(&a.2).then(a.1.cmp(&b.1)));
+
+    let new_label: HashMap<usize, usize> = order
+        .iter()
+        .enumerate()
+        .map(|(i, &(l, _, _))| (l, i))
+        .collect();
+    labels.iter().map(|l| new_label[l]).collect()
+}
+
+/// Newman-Girvan modularity of `labels` with a resolution parameter.
+pub fn modularity(csr: &Csr, labels: &[usize], resolution: f64) -> f64 {
+    let g = Aggregate::from_csr(csr);
+    if g.total == 0. {
+        return 0.;
+    }
+    let k = labels.iter().max().map_or(0, |x| x + 1);
+    let mut internal = vec![0.; k];
+    let mut tot = vec![0.; k];
+    for v in 0..g.size() {
+        tot[labels[v]] += g.strength[v];
+        for &(u, w) in g.adj[v].iter() {
+            if labels[u] == labels[v] {
+                internal[labels[v]] += w;
+            }
+        }
+    }
+
+    (0..k)
+        .map(|c| internal[c] / g.total - resolution * (tot[c] / g.total).powi(2))
+        .sum()
+}
+
+/// Move nodes between communities in sweeps until no move improves modularity (Louvain).
+/// Returns true if any node moved.
+fn local_moving(g: &Aggregate, partition: &mut [usize], resolution: f64, rng: &mut StdRng) -> bool {
+    let mut tot = vec![0.; g.size()];
+    for v in 0..g.size() {
+        tot[partition[v]] += g.strength[v];
+    }
+
+    let mut order: Vec<usize> = (0..g.size()).collect();
+    let mut moved_any = false;
+    loop {
+        order.shuffle(rng);
+        let mut moved = false;
+        for &v in order.iter() {
+            if let Some(best) = best_community(g, partition, &mut tot, v, resolution) {
+                partition[v] = best;
+                moved = true;
+            }
+        }
+        if !moved {
+            break;
+        }
+        moved_any = true;
+    }
+    moved_any
+}
+
+/// Remove `v` from its community and put it back into the community with the largest
+/// modularity gain. Returns the new community if `v` moved.
+fn best_community(
+    g: &Aggregate,
+    partition: &[usize],
+    tot: &mut [f64],
+    v: usize,
+    resolution: f64,
+) -> Option<usize> {
+    let current = partition[v];
+    let kv = g.strength[v];
+    tot[current] -= kv;
+
+    let weights = g.community_weights(v, partition);
+    let gain = |c: usize, tot: &[f64]| {
+        weights.get(&c).cloned().unwrap_or(0.) - resolution * kv * tot[c] / g.total
+    };
+
+    let mut best = current;
+    let mut best_gain = gain(current, tot);
+    for &c in weights.keys() {
+        let g = gain(c, tot);
+        if g > best_gain + 1e-12 {
+            best = c;
+            best_gain = g;
+        }
+    }
+
+    tot[best] += kv;
+    if best != current {
+        Some(best)
+    } else {
+        None
+    }
+}
+
+/// Queue based local moving of the Leiden algorithm. Only neighbours of moved nodes are
+/// revisited. Nodes may also move into an empty community.
+fn fast_local_moving(g: &Aggregate, partition: &mut [usize], resolution: f64, rng: &mut StdRng) {
+    let n = g.size();
+    let mut tot = vec![0.; n];
+    let mut members = vec![0usize; n];
+    for v in 0..n {
+        tot[partition[v]] += g.strength[v];
+        members[partition[v]] += 1;
+    }
+    let mut empty: Vec<usize> = (0..n).filter(|&c| members[c] == 0).collect();
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.shuffle(rng);
+    let mut queue: VecDeque<usize> = order.into_iter().collect();
+    let mut in_queue = vec![true; n];
+
+    while let Some(v) = queue.pop_front() {
+        in_queue[v] = false;
+        let current = partition[v];
+        let kv = g.strength[v];
+        tot[current] -= kv;
+        members[current] -= 1;
+        if members[current] == 0 {
+            empty.push(current);
+        }
+
+        let weights = g.community_weights(v, partition);
+        let mut best = current;
+        let mut best_gain =
+            weights.get(&current).cloned().unwrap_or(0.) - resolution * kv * tot[current] / g.total;
+        for (&c, &w) in weights.iter() {
+            let gain = w - resolution * kv * tot[c] / g.total;
+            if gain > best_gain + 1e-12 {
+                best = c;
+                best_gain = gain;
+            }
+        }
+        if best_gain < -1e-12 {
+            // an empty community has zero gain; `empty` may hold stale entries
+            while let Some(&c) = empty.last() {
+                if members[c] == 0 {
+                    best = c;
+                    break;
+                }
+                empty.pop();
+            }
+        }
+        tot[best] += kv;
+        members[best] += 1;
+        partition[v] = best;
+
+        if best != current {
+            for &(u, _) in g.adj[v].iter() {
+                if !in_queue[u] && partition[u] != best {
+                    in_queue[u] = true;
+                    queue.push_back(u);
+                }
+            }
+        }
+    }
+}
+
+/// Refinement phase of the Leiden algorithm. Each community of `partition` is split into
+/// well-connected subcommunities by greedily merging singletons.
+fn refine(g: &Aggregate, partition: &[usize], resolution: f64, rng: &mut StdRng) -> Vec<usize> {
+    let n = g.size();
+    let mut refined: Vec<usize> = (0..n).collect();
+    let mut size = vec![1usize; n];
+    let mut refined_tot: Vec<f64> = g.strength.clone();
+
+    let mut community_tot: HashMap<usize, f64> = HashMap::new();
+    for (c, k) in partition.iter().zip(g.strength.iter()) {
+        *community_tot.entry(*c).or_default() += k;
+    }
+
+    // weight from each refined subcommunity to the rest of its community
+    let mut external: Vec<f64> = (0..n)
+        .map(|v| {
+            g.adj[v]
+                .iter()
+                .filter(|&&(u, _)| partition[u] == partition[v])
+                .map(|x| x.1)
+                .sum()
+        })
+        .collect();
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.shuffle(rng);
+    for v in order {
+        if size[refined[v]] != 1 {
+            continue;
+        }
+        let s_tot = community_tot[&partition[v]];
+        let kv = g.strength[v];
+        let well_connected = |ext: f64, k: f64| ext >= resolution * k * (s_tot - k) / g.total;
+        if !well_connected(external[v], kv) {
+            continue;
+        }
+
+        let mut weights: BTreeMap<usize, f64> = BTreeMap::new();
+        for &(u, w) in g.adj[v].iter() {
+            if partition[u] == partition[v] {
+                *weights.entry(refined[u]).or_default() += w;
+            }
+        }
+
+        let mut best: Option<(usize, f64)> = None;
+        for (&t, &w) in weights.iter() {
+            if t == refined[v] || !well_connected(external[t], refined_tot[t]) {
+                continue;
+            }
+            let gain = w - resolution * kv * refined_tot[t] / g.total;
+            if gain >= 0. && best.is_none_or(|(_, bg)| gain > bg) {
+                best = Some((t, gain));
+            }
+        }
+
+        if let Some((t, _)) = best {
+            let own = refined[v];
+            external[t] += external[own] - 2. * weights[&t];
+            refined_tot[t] += kv;
+            size[t] += 1;
+            size[own] = 0;
+            refined[v] = t;
+        }
+    }
+
+    refined
+}
+
+/// Renumber labels to 0..k in order of first appearance.
+fn compact(labels: &mut [usize]) -> usize {
+    let mut map: HashMap<usize, usize> = HashMap::new();
+    for l in labels.iter_mut() {
+        let next = map.len();
+        *l = *map.entry(*l).or_insert(next);
+    }
+    map.len()
+}
+
+/// Louvain community detection (Blondel et al. 2008).
+pub fn louvain(csr: &Csr, resolution: f64, seed: u64) -> Vec<usize> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut g = Aggregate::from_csr(csr);
+    let mut membership: Vec<usize> = (0..csr.size()).collect();
+
+    loop {
+        let mut partition: Vec<usize> = (0..g.size()).collect();
+        if !local_moving(&g, &mut partition, resolution, &mut rng) {
+            break;
+        }
+        let k = compact(&mut partition);
+        membership.iter_mut().for_each(|m| *m = partition[*m]);
+        if k == g.size() {
+            break;
+        }
+        g = g.aggregate(&partition);
+    }
+
+    relabel_by_size(&membership)
+}
+
+/// Leiden community detection (Traag et al. 2019) with greedy refinement.
+pub fn leiden(csr: &Csr, resolution: f64, seed: u64) -> Vec<usize> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut g = Aggregate::from_csr(csr);
+    // membership: original node -> node of the aggregate graph
+    let mut membership: Vec<usize> = (0..csr.size()).collect();
+    let mut partition: Vec<usize> = (0..g.size()).collect();
+
+    loop {
+        fast_local_moving(&g, &mut partition, resolution, &mut rng);
+        let k = compact(&mut partition);
+        if k == g.size() {
+            break;
+        }
+
+        let mut refined = refine(&g, &partition, resolution, &mut rng);
+        let mut n_refined = compact(&mut refined);
+        if n_refined == g.size() {
+            // nothing merged in refinement; aggregate by the partition itself
+            refined = partition.clone();
+            n_refined = k;
+        }
+        membership.iter_mut().for_each(|m| *m = refined[*m]);
+
+        // the aggregate graph starts from the unrefined partition
+        let mut next_partition = vec![0; n_refined];
+        for v in 0..g.size() {
+            next_partition[refined[v]] = partition[v];
+        }
+        g = g.aggregate(&refined);
+        partition = next_partition;
+    }
+
+    let labels: Vec<usize> = membership.iter().map(|&m| partition[m]).collect();
+    relabel_by_size(&labels)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::*;
+
+    // two 4-cliques joined by a single edge
+    fn two_cliques() -> Csr {
+        let mut pairs = vec![];
+        for offset in [0, 4] {
+            for i in 0..4 {
+                for j in i + 1..4 {
+                    pairs.push((offset + i, offset + j));
+                }
+            }
+        }
+        pairs.push((3, 4));
+        Csr::from_pairs(8, &pairs)
+    }
+
+    #[test]
+    fn test_modularity_1() {
+        let csr = two_cliques();
+        let labels = vec![0, 0, 0, 0, 1, 1, 1, 1];
+        // 13 edges, 6 internal in each clique, degree sum 13 in each half
+        let expected = 12. / 13. - 2. * (13f64 / 26.).powi(2);
+        assert_abs_diff_eq!(modularity(&csr, &labels, 1.), expected);
+        assert_abs_diff_eq!(modularity(&csr, &[0; 8], 1.), 0.);
+    }
+
+    #[test]
+    fn test_louvain_1() {
+        let csr = two_cliques();
+        for seed in 0..5 {
+            let labels = louvain(&csr, 1., seed);
+            assert_eq!(labels[0..4], [labels[0]; 4]);
+            assert_eq!(labels[4..8], [labels[4]; 4]);
+            assert_ne!(labels[0], labels[4]);
+        }
+    }
+
+    #[test]
+    fn test_leiden_1() {
+        let csr = two_cliques();
+        for seed in 0..5 {
+            let labels = leiden(&csr, 1., seed);
+            assert_eq!(labels[0..4], [labels[0]; 4]);
+            assert_eq!(labels[4..8], [labels[4]; 4]);
+            assert_ne!(labels[0], labels[4]);
+        }
+    }
+
+    #[test]
+    fn test_relabel_by_size_1() {
+        assert_eq!(relabel_by_size(&[5, 3, 3, 7, 3, 5]), vec![1, 0, 0, 2, 0, 1]);
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::graph::{pair_key, RankedEdge};
+use crate::math;
+use crate::similarity::{self, RankMetric};
+
+/// Overlap of the edge sets of two networks on the same node index.
+#[derive(Debug, Clone, Serialize)]
+pub struct EdgeOverlap {
+    pub edges_a: usize,
+    pub edges_b: usize,
+    pub shared: usize,
+    pub only_a: usize,
+    pub only_b: usize,
+    pub union: usize,
+    pub jaccard: f64,
+    /// Spearman correlation of the ranks of shared edges
+    pub rank_spearman: f64,
+    /// Pearson correlation of the correlation coefficients of shared edges
+    pub corr_pearson: f64,
+}
+
+pub fn edge_overlap(a: &[RankedEdge], b: &[RankedEdge]) -> EdgeOverlap {
+    let b_edges: HashMap<(usize, usize), (f64, f64)> = b
+        .iter()
+        .map(|&(i, j, corr, rank)| (pair_key(i, j), (corr, rank)))
+        .collect();
+    let a_edges: HashMap<(usize, usize), (f64, f64)> = a
+        .iter()
+        .map(|&(i, j, corr, rank)| (pair_key(i, j), (corr, rank)))
+        .collect();
+
+    let (mut corr_a, mut corr_b, mut rank_a, mut rank_b) = (vec![], vec![], vec![], vec![]);
+    for (key, &(corr, rank)) in a_edges.iter() {
+        if let Some(&(other_corr, other_rank)) = b_edges.get(key) {
+            corr_a.push(corr);
+            corr_b.push(other_corr);
+            rank_a.push(rank);
+            rank_b.push(other_rank);
+        }
+    }
+
+    let shared = rank_a.len();
+    let union = a_edges.len() + b_edges.len() - shared;
+    EdgeOverlap {
+        edges_a: a_edges.len(),
+        edges_b: b_edges.len(),
+        shared,
+        only_a: a_edges.len() - shared,
+        only_b: b_edges.len() - shared,
+        union,
+        jaccard: shared as f64 / union as f64,
+        rank_spearman: math::spearman(&rank_a, &rank_b),
+        corr_pearson: math::pearson(&corr_a, &corr_b),
+    }
+}
+
+/// How the neighborhood of a gene differs between two networks.
+#[derive(Debug, Clone)]
+pub struct NeighborhoodChange {
+    pub degree_a: usize,
+    pub degree_b: usize,
+    pub shared_neighbors: usize,
+    /// Jaccard index of all neighbors
+    pub jaccard: f64,
+    /// `metric` of the top k neighbors in `a` against those in `b`
+    pub score: f64,
+}
+
+/// Compare the neighbor rankings (best first, as `similarity::ranked_adjacency`) of every
+/// node. A node without neighbors in one network scores 0. For COSMIX, whose reference must
+/// hold k elements, k is cut to the degree in `b`.
+pub fn neighborhood_change(
+    adj_a: &[Vec<usize>],
+    adj_b: &[Vec<usize>],
+    k: usize,
+    metric: &RankMetric,
+    rbo_p: f64,
+) -> Vec<NeighborhoodChange> {
+    adj_a
+        .iter()
+        .zip(adj_b)
+        .map(|(a, b)| {
+            let set_a: HashSet<&usize> = a.iter().collect();
+            let shared = b.iter().filter(|x| set_a.contains(x)).count();
+            let union = a.len() + b.len() - shared;
+            let score = if a.is_empty() || b.is_empty() {
+                0.
+            } else {
+                let k = match metric {
+                    RankMetric::COSMIX => k.min(b.len()),
+                    _ => k,
+                };
+                similarity::list_similarity(metric, a, b, k, rbo_p)
+            };
+            NeighborhoodChange {
+                degree_a: a.len(),
+                degree_b: b.len(),
+                shared_neighbors: shared,
+                jaccard: if union == 0 {
+                    f64::NAN
+                } else {
+                    shared as f64 / union as f64
+                },
+                score,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::*;
+
+    #[test]
+    fn test_edge_overlap_1() {
+        let a = vec![(0, 1, 0.9, 1.), (1, 2, 0.8, 2.), (2, 3, 0.7, 3.)];
+        // the same edges in either orientation, one missing and one new
+        let b = vec![(1, 0, 0.9, 1.), (3, 2, 0.6, 5.), (0, 3, 0.5, 4.)];
+        let overlap = edge_overlap(&a, &b);
+        assert_eq!(overlap.shared, 2);
+        assert_eq!((overlap.only_a, overlap.only_b, overlap.union), (1, 1, 4));
+        assert_abs_diff_eq!(overlap.jaccard, 0.5);
+        assert_abs_diff_eq!(overlap.rank_spearman, 1.);
+    }
+
+    #[test]
+    fn test_neighborhood_change_1() {
+        let adj_a = vec![vec![1, 2, 3], vec![0], vec![]];
+        let adj_b = vec![vec![1, 3], vec![], vec![0]];
+        let change = neighborhood_change(&adj_a, &adj_b, 3, &RankMetric::Jaccard, 0.9);
+        assert_eq!(change[0].shared_neighbors, 2);
+        assert_abs_diff_eq!(change[0].jaccard, 2. / 3.);
+        assert_abs_diff_eq!(change[0].score, 2. / 3.);
+        assert_abs_diff_eq!(change[1].score, 0.);
+        assert_abs_diff_eq!(change[2].jaccard, 0.);
+
+        // COSMIX against a shorter neighbor list in b
+        let change = neighborhood_change(&adj_a, &adj_b, 3, &RankMetric::COSMIX, 0.9);
+        assert_abs_diff_eq!(change[0].score, (1. + 1.) / 3.);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::graph::Csr;
+
+/// Disjoint-set forest with union by size and path halving.
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl UnionFind {
+    pub fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+        }
+    }
+
+    pub fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    pub fn union(&mut self, a: usize, b: usize) {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+    }
+
+    /// Component label of each element. Components are numbered from 0 in order of their
+    /// smallest element.
+    pub fn labels(&mut self) -> Vec<usize> {
+        let n = self.parent.len();
+        let mut root_label = vec![usize::MAX; n];
+        let mut labels = vec![0; n];
+        let mut n_components = 0;
+        for (x, label) in labels.iter_mut().enumerate() {
+            let root = self.find(x);
+            if root_label[root] == usize::MAX {
+                root_label[root] = n_components;
+                n_components += 1;
+            }
+            *label = root_label[root];
+        }
+        labels
+    }
+}
+
+/// Label connected components of `n` nodes joined by `pairs`.
+pub fn connected_components_from_pairs(n: usize, pairs: &[(usize, usize)]) -> Vec<usize> {
+    let mut uf = UnionFind::new(n);
+    for &(a, b) in pairs.iter() {
+        uf.union(a, b);
+    }
+    uf.labels()
+}
+
+/// Label connected components. Components are numbered from 0 in order of their
+/// smallest node index.
+pub fn connected_components(csr: &Csr) -> Vec<usize> {
+    let mut uf = UnionFind::new(csr.size());
+    for v in 0..csr.size() {
+        for &u in csr.neighbors(v) {
+            uf.union(v, u);
+        }
+    }
+    uf.labels()
+}
+
+/// Size of each component, indexed by component label.
+pub fn component_sizes(labels: &[usize]) -> Vec<usize> {
+    let n = labels.iter().max().map_or(0, |x| x + 1);
+    let mut sizes = vec![0; n];
+    for &l in labels {
+        sizes[l] += 1;
+    }
+    sizes
+}
+
+/// Core number of each node by the O(m) bucket algorithm of Batagelj and Zaversnik.
+pub fn core_numbers(csr: &Csr) -> Vec<usize> {
+    let n = csr.size();
+    let mut degree: Vec<usize> = (0..n).map(|v| csr.degree(v)).collect();
+    let max_degree = degree.iter().cloned().max().unwrap_or(0);
+
+    // bin[d]: start position of nodes with degree d in `order`
+    let mut bin = vec![0; max_degree + 1];
+    for &d in degree.iter() {
+        bin[d] += 1;
+    }
+    let mut start = 0;
+    for b in bin.iter_mut() {
+        let count = *b;
+        *b = start;
+        start += count;
+    }
+
+    let mut pos = vec![0; n];
+    let mut order = vec![0; n];
+    for v in 0..n {
+        pos[v] = bin[degree[v]];
+        order[pos[v]] = v;
+        bin[degree[v]] += 1;
+    }
+    for d in (1..=max_degree).rev() {
+        bin[d] = bin[d - 1];
+    }
+    bin[0] = 0;
+
+    for i in 0..n {
+        let v = order[i];
+        for &u in csr.neighbors(v) {
+            if degree[u] > degree[v] {
+                // move u to the front of its bin and shrink its degree
+                let du = degree[u];
+                let pu = pos[u];
+                let pw = bin[du];
+                let w = order[pw];
+                if u != w {
+                    order.swap(pu, pw);
+                    pos[u] = pw;
+                    pos[w] = pu;
+                }
+                bin[du] += 1;
+                degree[u] -= 1;
+            }
+        }
+    }
+
+    degree
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::{Edge, Graph};
+
+    #[test]
+    fn test_connected_components_1() {
+        let nodes: Vec<String> = (0..6).map(|x| x.to_string()).collect();
+        let edges = vec![
+            Edge::new(0, 1, 0.9, 1.),
+            Edge::new(1, 2, 0.8, 1.),
+            Edge::new(3, 4, 0.7, 1.),
+        ];
+        let csr = Graph::from_edges(&nodes, &edges).to_csr();
+
+        let labels = connected_components(&csr);
+        assert_eq!(labels, vec![0, 0, 0, 1, 1, 2]);
+        assert_eq!(component_sizes(&labels), vec![3, 2, 1]);
+        assert_eq!(
+            connected_components_from_pairs(6, &[(4, 3), (2, 1), (0, 1)]),
+            labels
+        );
+    }
+
+    #[test]
+    fn test_core_numbers_1() {
+        // 4-clique {0, 1, 2, 3} with a triangle {3, 4, 5} and a pendant 6
+        let pairs = vec![
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (1, 2),
+            (1, 3),
+            (2, 3),
+            (3, 4),
+            (3, 5),
+            (4, 5),
+            (5, 6),
+        ];
+        let csr = Csr::from_pairs(8, &pairs);
+        assert_eq!(core_numbers(&csr), vec![3, 3, 3, 3, 2, 2, 1, 0]);
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::{HashMap, HashSet};
+
+use structopt::clap::arg_enum;
+
+use crate::graph::{pair_key, RankedEdge};
+use crate::math;
+
+arg_enum! {
+    #[derive(Debug)]
+    pub enum Aggregation {
+        MeanRank,
+        MedianRank,
+        FisherZ,
+        Support,
+    }
+}
+
+/// Edges of one dataset on the shared gene index.
+#[derive(Debug, Clone)]
+pub struct Dataset {
+    pub edges: Vec<RankedEdge>,
+    /// genes of the shared index measured in this dataset
+    pub genes: Vec<bool>,
+    /// sample count, known for expression matrices and given for networks with `--samples`
+    pub samples: Option<usize>,
+}
+
+impl Dataset {
+    /// Rank given to a pair of measured genes missing from the edges, just beyond the
+    /// largest rank kept.
+    fn missing_rank(&self) -> f64 {
+        self.edges.iter().map(|e| e.3).fold(0., f64::max) + 1.
+    }
+}
+
+/// A gene pair of the consensus network.
+#[derive(Debug, Clone)]
+pub struct ConsensusEdge {
+    pub gene_1: usize,
+    pub gene_2: usize,
+    /// Fisher z combined correlation over the supporting datasets
+    pub corr: f64,
+    /// datasets with the edge
+    pub support: usize,
+    /// datasets measuring both genes
+    pub datasets: usize,
+    pub mean_rank: f64,
+    pub median_rank: f64,
+}
+
+/// Average correlations on the Fisher z scale with the given weights, or equally if the
+/// weights sum to 0.
+pub fn fisher_z_combine(corrs: &[(f64, f64)]) -> f64 {
+    let (sum, weights) = corrs.iter().fold((0., 0.), |(sum, weights), &(r, w)| {
+        (sum + w * math::fisher_z(r), weights + w)
+    });
+    if weights > 0. {
+        (sum / weights).tanh()
+    } else {
+        let sum: f64 = corrs.iter().map(|&(r, _)| math::fisher_z(r)).sum();
+        (sum / corrs.len() as f64).tanh()
+    }
+}
+
+/// Aggregate the edges of every dataset into one network, best first by `aggregation`.
+///
+/// Each pair is ranked in every dataset measuring both genes; a pair missing from the edges
+/// of such a dataset gets the rank just beyond its largest one. Pairs with fewer than
+/// `min_support` datasets are dropped.
+///
+/// Correlations are combined weighted by sample size (n - 3) if every dataset has one, and
+/// equally otherwise, so datasets of unknown size are not outweighed.
+pub fn consensus(
+    datasets: &[Dataset],
+    aggregation: &Aggregation,
+    min_support: usize,
+) -> Vec<ConsensusEdge> {
+    let observed: Vec<HashMap<(usize, usize), (f64, f64)>> = datasets
+        .iter()
+        .map(|d| {
+            d.edges
+                .iter()
+                .map(|&(i, j, corr, rank)| (pair_key(i, j), (corr, rank)))
+                .collect()
+        })
+        .collect();
+    let missing_ranks: Vec<f64> = datasets.iter().map(|d| d.missing_rank()).collect();
+    let weights: Vec<f64> = if datasets.iter().all(|d| d.samples.is_some()) {
+        datasets
+            .iter()
+            .map(|d| d.samples.unwrap_or(0).saturating_sub(3) as f64)
+            .collect()
+    } else {
+        vec![1.; datasets.len()]
+    };
+    let pairs: HashSet<(usize, usize)> = observed.iter().flat_map(|o| o.keys().cloned()).collect();
+
+    let mut edges: Vec<ConsensusEdge> = pairs
+        .into_iter()
+        .filter_map(|(i, j)| {
+            let mut ranks = vec![];
+            let mut corrs = vec![];
+            for (d, dataset) in datasets.iter().enumerate() {
+                match observed[d].get(&(i, j)) {
+                    Some(&(corr, rank)) => {
+                        ranks.push(rank);
+                        corrs.push((corr, weights[d]));
+                    }
+                    None if dataset.genes[i] && dataset.genes[j] => ranks.push(missing_ranks[d]),
+                    None => {}
+                }
+            }
+            if corrs.len() < min_support {
+                return None;
+            }
+            Some(ConsensusEdge {
+                gene_1: i,
+                gene_2: j,
+                corr: fisher_z_combine(&corrs),
+                support: corrs.len(),
+                datasets: ranks.len(),
+                mean_rank: math::mean(&ranks),
+                median_rank: math::median(&ranks),
+            })
+        })
+        .collect();
+
+    edges.sort_by(|a, b| {
+        let order = match aggregation {
+            Aggregation::MeanRank => a.mean_rank.total_cmp(&b.mean_rank),
+            Aggregation::MedianRank => a
+                .median_rank
+                .total_cmp(&b.median_rank)
+                .then(a.mean_rank.total_cmp(&b.mean_rank)),
+            Aggregation::FisherZ => b.corr.total_cmp(&a.corr),
+            Aggregation::Support => b
+                .support
+                .cmp(&a.support)
+                .then(a.mean_rank.total_cmp(&b.mean_rank)),
+        };
+        order.then((a.gene_1, a.gene_2).cmp(&(b.gene_1, b.gene_2)))
+    });
+    edges
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::*;
+
+    #[test]
+    fn test_consensus_1() {
+        let a = Dataset {
+            edges: vec![(0, 1, 0.9, 1.), (1, 2, 0.8, 2.), (2, 3, 0.5, 4.)],
+            genes: vec![true; 4],
+            samples: Some(13),
+        };
+        // the same pair in the other orientation, gene 3 not measured
+        let b = Dataset {
+            edges: vec![(1, 0, 0.5, 3.), (0, 2, 0.7, 1.)],
+            genes: vec![true, true, true, false],
+            samples: Some(23),
+        };
+        let edges = consensus(&[a.clone(), b.clone()], &Aggregation::MeanRank, 1);
+        assert_eq!(edges.len(), 4);
+        let first = &edges[0];
+        assert_eq!((first.gene_1, first.gene_2), (0, 1));
+        assert_eq!((first.support, first.datasets), (2, 2));
+        assert_abs_diff_eq!(first.mean_rank, 2.);
+        let expected = ((10. * 0.9f64.atanh() + 20. * 0.5f64.atanh()) / 30.).tanh();
+        assert_abs_diff_eq!(first.corr, expected, epsilon = 1e-12);
+
+        // (0, 2) missing in a takes rank 5, (2, 3) is only ranked by a
+        let ranks: HashMap<(usize, usize), (usize, f64)> = edges
+            .iter()
+            .map(|e| ((e.gene_1, e.gene_2), (e.datasets, e.mean_rank)))
+            .collect();
+        assert_eq!(ranks[&(0, 2)], (2, 3.));
+        assert_eq!(ranks[&(2, 3)], (1, 4.));
+
+        let edges = consensus(&[a.clone(), b.clone()], &Aggregation::Support, 2);
+        assert_eq!(edges.len(), 1);
+        let edges = consensus(&[a.clone(), b.clone()], &Aggregation::FisherZ, 1);
+        assert_eq!((edges[0].gene_1, edges[0].gene_2), (1, 2));
+
+        // a network of unknown size: equal weights
+        let network = Dataset { samples: None, ..b };
+        let edges = consensus(&[a.clone(), network], &Aggregation::MeanRank, 1);
+        let expected = ((0.9f64.atanh() + 0.5f64.atanh()) / 2.).tanh();
+        assert_abs_diff_eq!(edges[0].corr, expected, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_fisher_z_combine_1() {
+        assert_abs_diff_eq!(
+            fisher_z_combine(&[(0.5, 3.), (0.5, 1.)]),
+            0.5,
+            epsilon = 1e-12
+        );
+        // every dataset with 3 samples or fewer
+        let r = fisher_z_combine(&[(0.9, 0.), (0.5, 0.)]);
+        assert_abs_diff_eq!(
+            r,
+            ((0.9f64.atanh() + 0.5f64.atanh()) / 2.).tanh(),
+            epsilon = 1e-12
+        );
+    }
+}
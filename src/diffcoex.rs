@@ -0,0 +1,174 @@
+use std::collections::HashSet;
+
+use ndarray::Array2;
+use statrs::function::erf::erfc;
+
+use crate::math;
+use crate::rank::{self, Rank};
+use crate::sweep;
+
+/// Fisher z test of the difference of two Pearson correlations from independent samples of
+/// sizes `n_a` and `n_b`. Returns the z statistic and its two-sided p-value.
+pub fn fisher_z_test(r_a: f64, n_a: usize, r_b: f64, n_b: usize) -> (f64, f64) {
+    if n_a < 4 || n_b < 4 {
+        return (f64::NAN, f64::NAN);
+    }
+    let se = (1. / (n_a - 3) as f64 + 1. / (n_b - 3) as f64).sqrt();
+    let z = (math::fisher_z(r_b) - math::fisher_z(r_a)) / se;
+    (z, erfc(z.abs() / std::f64::consts::SQRT_2))
+}
+
+/// A gene pair tested for differential co-expression between conditions a and b.
+#[derive(Debug, Clone)]
+pub struct DiffEdge {
+    pub gene_1: usize,
+    pub gene_2: usize,
+    pub corr_a: f64,
+    pub corr_b: f64,
+    pub rank_a: f64,
+    pub rank_b: f64,
+    pub z: f64,
+    pub pvalue: f64,
+    pub padj: f64,
+}
+
+impl DiffEdge {
+    pub fn delta_corr(&self) -> f64 {
+        self.corr_b - self.corr_a
+    }
+
+    pub fn rank_change(&self) -> f64 {
+        self.rank_b - self.rank_a
+    }
+}
+
+/// Test every gene pair within `rank_cutoff` (HRR or MR) in either condition, with
+/// Benjamini-Hochberg adjusted p-values over the tested pairs. `n_a` and `n_b` are the
+/// sample sizes. Sorted by p-value.
+#[allow(clippy::too_many_arguments)]
+pub fn diff_edges(
+    corr_a: &Array2<f64>,
+    rank_a: &Array2<usize>,
+    n_a: usize,
+    corr_b: &Array2<f64>,
+    rank_b: &Array2<usize>,
+    n_b: usize,
+    method: &Rank,
+    rank_cutoff: f64,
+) -> Vec<DiffEdge> {
+    let pairs: HashSet<(usize, usize)> = sweep::ranked_edges(corr_a, rank_a, method, rank_cutoff)
+        .into_iter()
+        .chain(sweep::ranked_edges(corr_b, rank_b, method, rank_cutoff))
+        .map(|(i, j, _, _)| (i, j))
+        .collect();
+
+    let mut edges: Vec<DiffEdge> = pairs
+        .into_iter()
+        .map(|(i, j)| {
+            let (z, pvalue) = fisher_z_test(corr_a[[i, j]], n_a, corr_b[[i, j]], n_b);
+            DiffEdge {
+                gene_1: i,
+                gene_2: j,
+                corr_a: corr_a[[i, j]],
+                corr_b: corr_b[[i, j]],
+                rank_a: rank::pair_rank(rank_a, method, i, j),
+                rank_b: rank::pair_rank(rank_b, method, i, j),
+                z,
+                pvalue,
+                padj: f64::NAN,
+            }
+        })
+        .collect();
+    edges.sort_by(|a, b| {
+        a.pvalue
+            .total_cmp(&b.pvalue)
+            .then((a.gene_1, a.gene_2).cmp(&(b.gene_1, b.gene_2)))
+    });
+
+    let padj = math::benjamini_hochberg(&edges.iter().map(|e| e.pvalue).collect::<Vec<f64>>());
+    edges.iter_mut().zip(padj).for_each(|(e, p)| e.padj = p);
+    edges
+}
+
+/// Per-gene rewiring: how many of its tested edges changed significantly.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Rewiring {
+    pub tested: usize,
+    pub significant: usize,
+    /// significant edges with a stronger correlation in b
+    pub strengthened: usize,
+    /// significant edges with a weaker correlation in b
+    pub weakened: usize,
+    pub mean_abs_delta_corr: f64,
+}
+
+impl Rewiring {
+    pub fn fraction(&self) -> f64 {
+        self.significant as f64 / self.tested as f64
+    }
+}
+
+pub fn rewiring(n_genes: usize, edges: &[DiffEdge], padj_cutoff: f64) -> Vec<Rewiring> {
+    let mut genes = vec![Rewiring::default(); n_genes];
+    for e in edges.iter() {
+        for &g in [e.gene_1, e.gene_2].iter() {
+            let r = &mut genes[g];
+            r.tested += 1;
+            r.mean_abs_delta_corr += e.delta_corr().abs();
+            if e.padj <= padj_cutoff {
+                r.significant += 1;
+                if e.corr_b.abs() > e.corr_a.abs() {
+                    r.strengthened += 1;
+                } else {
+                    r.weakened += 1;
+                }
+            }
+        }
+    }
+    for r in genes.iter_mut().filter(|r| r.tested > 0) {
+        r.mean_abs_delta_corr /= r.tested as f64;
+    }
+    genes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::*;
+
+    #[test]
+    fn test_fisher_z_test_1() {
+        // atanh(0.8) - atanh(0.2) = 0.8959, se = sqrt(2 / 47)
+        let (z, p) = fisher_z_test(0.2, 50, 0.8, 50);
+        assert_abs_diff_eq!(z, 4.342938, epsilon = 1e-6);
+        assert!(p < 1e-4);
+        let (z, p) = fisher_z_test(0.5, 20, 0.5, 30);
+        assert_abs_diff_eq!(z, 0.);
+        assert_abs_diff_eq!(p, 1.);
+        assert!(fisher_z_test(0.5, 3, 0.5, 30).0.is_nan());
+    }
+
+    #[test]
+    fn test_rewiring_1() {
+        let edge = |gene_2, corr_b, padj| DiffEdge {
+            gene_1: 0,
+            gene_2,
+            corr_a: 0.5,
+            corr_b,
+            rank_a: 1.,
+            rank_b: 1.,
+            z: 0.,
+            pvalue: padj,
+            padj,
+        };
+        let edges = vec![edge(1, 0.9, 0.01), edge(2, 0.1, 0.01), edge(3, 0.5, 0.5)];
+        let genes = rewiring(4, &edges, 0.05);
+        assert_eq!(
+            (genes[0].tested, genes[0].significant, genes[0].strengthened),
+            (3, 2, 1)
+        );
+        assert_abs_diff_eq!(genes[0].mean_abs_delta_corr, 0.8 / 3.);
+        assert_abs_diff_eq!(genes[0].fraction(), 2. / 3.);
+        assert_eq!(genes[3].significant, 0);
+    }
+}
@@ -0,0 +1,87 @@
+use ndarray::{Array1, Array2, Axis};
+
+/// Scale each row to mean 0 and standard deviation 1 (ddof = 1).
+pub fn scale_rows(arr: &Array2<f64>) -> Array2<f64> {
+    let mut x = arr.to_owned();
+    for mut row in x.axis_iter_mut(Axis(0)) {
+        let mean = row.mean().unwrap_or(0.);
+        let sd = row.std(1.);
+        row.mapv_inplace(|v| if sd > 0. { (v - mean) / sd } else { 0. });
+    }
+    x
+}
+
+/// Module eigengene: the first principal component over samples of the scaled expression of
+/// the genes of a module (rows of `x`).
+///
+/// Returns the eigengene scaled to unit variance, oriented to correlate positively with the
+/// average scaled expression, and the fraction of variance it explains.
+pub fn eigengene(x: &Array2<f64>) -> (Array1<f64>, f64) {
+    let x = scale_rows(x);
+    let n_samples = x.ncols();
+    // samples x samples, small for expression data
+    let c = x.t().dot(&x);
+    let trace = c.diag().sum();
+
+    // rows are centered, so a uniform start vector would lie in the null space;
+    // start from the column of the largest diagonal element instead
+    let start = (0..n_samples)
+        .max_by(|&a, &b| c[[a, a]].total_cmp(&c[[b, b]]))
+        .unwrap_or(0);
+    let mut v = c.column(start).to_owned();
+    let mut lambda = 0.;
+    for _ in 0..1000 {
+        let next = c.dot(&v);
+        let norm = next.dot(&next).sqrt();
+        if norm == 0. {
+            break;
+        }
+        let next = next / norm;
+        let diff = (&next - &v).mapv(f64::abs).sum();
+        v = next;
+        lambda = norm;
+        if diff < 1e-12 {
+            break;
+        }
+    }
+
+    let average = x.mean_axis(Axis(0)).unwrap();
+    if v.dot(&average) < 0. {
+        v.mapv_inplace(|a| -a);
+    }
+    let sd = v.std(1.);
+    if sd > 0. {
+        let mean = v.mean().unwrap();
+        v.mapv_inplace(|a| (a - mean) / sd);
+    }
+
+    let explained = if trace > 0. { lambda / trace } else { 0. };
+    (v, explained)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math;
+    use approx::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_eigengene_1() {
+        // two perfectly correlated genes and one anti-correlated gene
+        let x = array![[1., 2., 3., 4.], [2., 4., 6., 8.], [4., 3., 2., 1.]];
+        let (me, explained) = eigengene(&x);
+        assert_abs_diff_eq!(explained, 1., epsilon = 1e-9);
+        assert_abs_diff_eq!(
+            math::pearson(&me.to_vec(), &[1., 2., 3., 4.]),
+            1.,
+            epsilon = 1e-9
+        );
+        assert_abs_diff_eq!(me.std(1.), 1., epsilon = 1e-9);
+
+        let x = array![[1., 2., 3., 4.], [1., 3., 2., 4.]];
+        let (_, explained) = eigengene(&x);
+        // eigenvalues of the 2x2 correlation matrix are 1 +- 0.8
+        assert_abs_diff_eq!(explained, 0.9, epsilon = 1e-9);
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use rayon::prelude::*;
+
+use crate::math;
+
+/// Gene to annotated terms.
+pub type Annotation = HashMap<String, HashSet<String>>;
+
+/// One `[Term]` stanza of an OBO file.
+#[derive(Debug, Clone, Default)]
+pub struct OboTerm {
+    pub id: String,
+    pub name: String,
+    pub namespace: String,
+    pub alt_ids: Vec<String>,
+    /// `is_a` and `part_of` parents
+    pub parents: Vec<String>,
+    pub obsolete: bool,
+}
+
+/// Term DAG used to propagate annotations to ancestor terms.
+#[derive(Debug, Clone, Default)]
+pub struct Ontology {
+    parents: HashMap<String, Vec<String>>,
+    names: HashMap<String, String>,
+    alt_ids: HashMap<String, String>,
+}
+
+impl Ontology {
+    pub fn new(terms: &[OboTerm]) -> Self {
+        let mut ontology = Self::default();
+        for t in terms.iter().filter(|t| !t.obsolete) {
+            ontology.parents.insert(t.id.clone(), t.parents.clone());
+            ontology.names.insert(t.id.clone(), t.name.clone());
+            for alt in t.alt_ids.iter() {
+                ontology.alt_ids.insert(alt.clone(), t.id.clone());
+            }
+        }
+        ontology
+    }
+
+    pub fn name(&self, term: &str) -> Option<&String> {
+        self.names.get(term)
+    }
+
+    /// The primary id of `term`, resolving alternative ids. `None` for unknown terms.
+    pub fn resolve<'a>(&'a self, term: &'a str) -> Option<&'a str> {
+        if self.parents.contains_key(term) {
+            Some(term)
+        } else {
+            self.alt_ids.get(term).map(|x| x.as_str())
+        }
+    }
+
+    /// `term` and all its ancestors.
+    pub fn ancestors(&self, term: &str) -> HashSet<String> {
+        let mut res = HashSet::new();
+        let mut stack = vec![term.to_string()];
+        while let Some(t) = stack.pop() {
+            if let Some(parents) = self.parents.get(&t) {
+                stack.extend(parents.iter().filter(|p| !res.contains(*p)).cloned());
+            }
+            res.insert(t);
+        }
+        res
+    }
+
+    /// Add the ancestors of every annotated term (true path rule). Terms missing from the
+    /// ontology are kept as they are; their number is returned.
+    pub fn propagate(&self, annotation: &mut Annotation) -> usize {
+        let mut cache: HashMap<String, HashSet<String>> = HashMap::new();
+        let mut unknown: HashSet<String> = HashSet::new();
+        for terms in annotation.values_mut() {
+            let mut propagated = HashSet::new();
+            for t in terms.iter() {
+                match self.resolve(t) {
+                    Some(id) => propagated.extend(
+                        cache
+                            .entry(id.to_string())
+                            .or_insert_with(|| self.ancestors(id))
+                            .iter()
+                            .cloned(),
+                    ),
+                    None => {
+                        unknown.insert(t.clone());
+                        propagated.insert(t.clone());
+                    }
+                }
+            }
+            *terms = propagated;
+        }
+        unknown.len()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Enrichment {
+    pub set: String,
+    pub term: String,
+    /// annotated genes of the set in the background
+    pub set_size: usize,
+    /// genes of the term in the background
+    pub term_size: usize,
+    pub background_size: usize,
+    pub overlap: Vec<String>,
+    pub pvalue: f64,
+    pub padj: f64,
+}
+
+impl Enrichment {
+    pub fn expected(&self) -> f64 {
+        self.set_size as f64 * self.term_size as f64 / self.background_size as f64
+    }
+}
+
+/// Hypergeometric test of every term overlapping every gene set.
+///
+/// `annotation` maps genes to terms and should be restricted to the background, which is
+/// the set of annotated genes. p-values are adjusted by Benjamini-Hochberg within each set,
+/// over the terms that share at least one gene with it.
+pub fn enrichment(
+    sets: &[(String, Vec<String>)],
+    annotation: &Annotation,
+    min_term_size: usize,
+    max_term_size: usize,
+) -> Vec<Enrichment> {
+    let mut term_size: HashMap<&str, usize> = HashMap::new();
+    for terms in annotation.values() {
+        for t in terms.iter() {
+            *term_size.entry(t.as_str()).or_insert(0) += 1;
+        }
+    }
+    let background = annotation.len();
+
+    sets.par_iter()
+        .flat_map_iter(|(name, genes)| {
+            let genes: HashSet<&String> = genes
+                .iter()
+                .filter(|g| annotation.contains_key(*g))
+                .collect();
+            let mut overlap: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+            for g in genes.iter() {
+                for t in annotation[*g].iter() {
+                    let size = term_size[t.as_str()];
+                    if size >= min_term_size && size <= max_term_size {
+                        overlap.entry(t.as_str()).or_default().push(g.to_string());
+                    }
+                }
+            }
+
+            let mut res: Vec<Enrichment> = overlap
+                .into_iter()
+                .map(|(t, mut overlap)| {
+                    overlap.sort_unstable();
+                    Enrichment {
+                        set: name.clone(),
+                        term: t.to_string(),
+                        set_size: genes.len(),
+                        term_size: term_size[t],
+                        background_size: background,
+                        pvalue: math::hypergeometric_sf(
+                            overlap.len() as u64,
+                            background as u64,
+                            term_size[t] as u64,
+                            genes.len() as u64,
+                        ),
+                        overlap,
+                        padj: 1.,
+                    }
+                })
+                .collect();
+
+            let pvalues: Vec<f64> = res.iter().map(|e| e.pvalue).collect();
+            for (e, padj) in res.iter_mut().zip(math::benjamini_hochberg(&pvalues)) {
+                e.padj = padj;
+            }
+            res.sort_by(|a, b| a.pvalue.partial_cmp(&b.pvalue).unwrap());
+            res.into_iter()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::*;
+
+    fn term(id: &str, parents: &[&str]) -> OboTerm {
+        OboTerm {
+            id: id.to_string(),
+            parents: parents.iter().map(|x| x.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_propagate_1() {
+        // root <- a <- c, root <- b <- c
+        let mut alt = term("c", &["a", "b"]);
+        alt.alt_ids.push("c_old".to_string());
+        let ontology = Ontology::new(&[
+            term("root", &[]),
+            term("a", &["root"]),
+            term("b", &["root"]),
+            alt,
+        ]);
+
+        let mut annotation: Annotation = HashMap::new();
+        annotation.insert(
+            "g1".to_string(),
+            vec!["c_old".to_string()].into_iter().collect(),
+        );
+        annotation.insert(
+            "g2".to_string(),
+            vec!["x".to_string()].into_iter().collect(),
+        );
+        assert_eq!(ontology.propagate(&mut annotation), 1);
+
+        let mut g1: Vec<&String> = annotation["g1"].iter().collect();
+        g1.sort();
+        assert_eq!(g1, ["a", "b", "c", "root"]);
+        assert_eq!(annotation["g2"].len(), 1);
+    }
+
+    #[test]
+    fn test_enrichment_1() {
+        // 10 annotated genes, term t on g0..g3, set g0, g1, g5
+        let annotation: Annotation = (0..10)
+            .map(|i| {
+                let t = if i < 4 { "t" } else { "u" };
+                (format!("g{}", i), vec![t.to_string()].into_iter().collect())
+            })
+            .collect();
+        let sets = vec![(
+            "s".to_string(),
+            vec![
+                "g0".to_string(),
+                "g1".to_string(),
+                "g5".to_string(),
+                "g99".to_string(),
+            ],
+        )];
+        let res = enrichment(&sets, &annotation, 1, usize::MAX);
+
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[0].term, "t");
+        assert_eq!(res[0].set_size, 3);
+        assert_eq!(res[0].overlap, ["g0", "g1"]);
+        assert_abs_diff_eq!(res[0].pvalue, 1. / 3., epsilon = 1e-12);
+        assert_abs_diff_eq!(res[0].expected(), 1.2, epsilon = 1e-12);
+    }
+}
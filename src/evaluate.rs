@@ -0,0 +1,246 @@
+use std::collections::{HashMap, HashSet};
+
+use rayon::prelude::*;
+
+use crate::enrich::Annotation;
+
+/// Positive gene pairs to evaluate networks against. Pairs of covered genes that are not
+/// positive are negatives; pairs involving any other gene are not evaluated.
+#[derive(Debug, Clone)]
+pub enum GoldStandard {
+    /// an explicit list of pairs, e.g. protein interactions
+    Pairs {
+        index: HashMap<String, usize>,
+        pairs: HashSet<(usize, usize)>,
+    },
+    /// genes sharing an annotation term, e.g. GO or complexes; terms are sorted per gene
+    Terms {
+        index: HashMap<String, usize>,
+        terms: Vec<Vec<usize>>,
+    },
+}
+
+impl GoldStandard {
+    pub fn from_pairs(pairs: &[(String, String)]) -> Self {
+        let mut index: HashMap<String, usize> = HashMap::new();
+        let mut set = HashSet::new();
+        for (a, b) in pairs.iter() {
+            let n = index.len();
+            let i = *index.entry(a.clone()).or_insert(n);
+            let n = index.len();
+            let j = *index.entry(b.clone()).or_insert(n);
+            if i != j {
+                set.insert((i.min(j), i.max(j)));
+            }
+        }
+        GoldStandard::Pairs { index, pairs: set }
+    }
+
+    /// Genes are positive pairs when they share a term annotating at most `max_term_size`
+    /// genes; broad terms say little about co-expression.
+    pub fn from_annotation(annotation: &Annotation, max_term_size: usize) -> Self {
+        let mut term_members: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (g, terms) in annotation.iter() {
+            for t in terms.iter() {
+                term_members.entry(t.as_str()).or_default().push(g.as_str());
+            }
+        }
+
+        let mut index: HashMap<String, usize> = HashMap::new();
+        let mut gene_terms: Vec<Vec<usize>> = vec![];
+        for (t, (_, members)) in term_members
+            .iter()
+            .filter(|(_, m)| m.len() >= 2 && m.len() <= max_term_size)
+            .enumerate()
+        {
+            for g in members.iter() {
+                let n = index.len();
+                let i = *index.entry(g.to_string()).or_insert(n);
+                if i == gene_terms.len() {
+                    gene_terms.push(vec![]);
+                }
+                gene_terms[i].push(t);
+            }
+        }
+        gene_terms.iter_mut().for_each(|t| t.sort_unstable());
+
+        GoldStandard::Terms {
+            index,
+            terms: gene_terms,
+        }
+    }
+
+    pub fn index(&self, gene: &str) -> Option<usize> {
+        match self {
+            GoldStandard::Pairs { index, .. } | GoldStandard::Terms { index, .. } => {
+                index.get(gene).cloned()
+            }
+        }
+    }
+
+    pub fn is_positive(&self, i: usize, j: usize) -> bool {
+        match self {
+            GoldStandard::Pairs { pairs, .. } => pairs.contains(&(i.min(j), i.max(j))),
+            GoldStandard::Terms { terms, .. } => {
+                // both lists are sorted
+                let (a, b) = (&terms[i], &terms[j]);
+                let (mut x, mut y) = (0, 0);
+                while x < a.len() && y < b.len() {
+                    match a[x].cmp(&b[y]) {
+                        std::cmp::Ordering::Equal => return true,
+                        std::cmp::Ordering::Less => x += 1,
+                        std::cmp::Ordering::Greater => y += 1,
+                    }
+                }
+                false
+            }
+        }
+    }
+
+    /// Number of positive pairs among `genes` (gold standard indices).
+    pub fn n_positives(&self, genes: &[usize]) -> usize {
+        match self {
+            GoldStandard::Pairs { pairs, .. } => {
+                let genes: HashSet<usize> = genes.iter().cloned().collect();
+                pairs
+                    .iter()
+                    .filter(|(a, b)| genes.contains(a) && genes.contains(b))
+                    .count()
+            }
+            GoldStandard::Terms { terms, .. } => {
+                let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+                for &g in genes.iter() {
+                    for &t in terms[g].iter() {
+                        members.entry(t).or_default().push(g);
+                    }
+                }
+                // count the distinct partners of each gene, with a per-thread marker
+                genes
+                    .par_iter()
+                    .map_init(HashSet::new, |seen: &mut HashSet<usize>, &g| {
+                        seen.clear();
+                        for t in terms[g].iter() {
+                            seen.extend(members[t].iter().filter(|&&x| x > g));
+                        }
+                        seen.len()
+                    })
+                    .sum()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CurvePoint {
+    /// edges with rank <= this are predicted positive
+    pub rank: f64,
+    pub edges: usize,
+    pub tp: usize,
+    pub fp: usize,
+}
+
+impl CurvePoint {
+    pub fn precision(&self) -> f64 {
+        self.tp as f64 / (self.tp + self.fp) as f64
+    }
+}
+
+/// Precision-recall and ROC points at every distinct rank threshold.
+/// `edges` are `(rank, is_positive)` of the evaluated edges.
+pub fn curve(edges: &mut [(f64, bool)]) -> Vec<CurvePoint> {
+    edges.par_sort_unstable_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    let mut res: Vec<CurvePoint> = vec![];
+    let (mut tp, mut fp) = (0, 0);
+    for (i, &(rank, positive)) in edges.iter().enumerate() {
+        if positive {
+            tp += 1;
+        } else {
+            fp += 1;
+        }
+        if i + 1 == edges.len() || edges[i + 1].0 != rank {
+            res.push(CurvePoint {
+                rank,
+                edges: i + 1,
+                tp,
+                fp,
+            });
+        }
+    }
+    res
+}
+
+/// Area under the ROC curve. Pairs missing from the network are ranked below every edge,
+/// so the curve is closed by a straight line to (1, 1).
+pub fn auroc(curve: &[CurvePoint], positives: usize, negatives: usize) -> f64 {
+    let (p, n) = (positives as f64, negatives as f64);
+    let mut area = 0.;
+    let (mut x, mut y) = (0., 0.);
+    for c in curve.iter() {
+        let (fpr, tpr) = (c.fp as f64 / n, c.tp as f64 / p);
+        area += (fpr - x) * (tpr + y) / 2.;
+        x = fpr;
+        y = tpr;
+    }
+    area + (1. - x) * (1. + y) / 2.
+}
+
+/// Area under the precision-recall curve as average precision. Positives missing from the
+/// network are never recalled.
+pub fn auprc(curve: &[CurvePoint], positives: usize) -> f64 {
+    let mut recall = 0.;
+    let mut area = 0.;
+    for c in curve.iter() {
+        let r = c.tp as f64 / positives as f64;
+        area += (r - recall) * c.precision();
+        recall = r;
+    }
+    area
+}
+
+/// The last point of the curve (the most edges) with precision of at least `precision`.
+pub fn at_precision(curve: &[CurvePoint], precision: f64) -> Option<&CurvePoint> {
+    curve.iter().rev().find(|c| c.precision() >= precision)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::*;
+
+    #[test]
+    fn test_gold_standard_1() {
+        let mut annotation: Annotation = HashMap::new();
+        for (g, t) in [("a", "x"), ("b", "x"), ("c", "x"), ("c", "y"), ("d", "y")].iter() {
+            annotation
+                .entry(g.to_string())
+                .or_default()
+                .insert(t.to_string());
+        }
+        let gold = GoldStandard::from_annotation(&annotation, 10);
+        let idx = |g: &str| gold.index(g).unwrap();
+        assert!(gold.is_positive(idx("a"), idx("c")));
+        assert!(!gold.is_positive(idx("a"), idx("d")));
+        // ab, ac, bc, cd
+        let all: Vec<usize> = ["a", "b", "c", "d"].iter().map(|g| idx(g)).collect();
+        assert_eq!(gold.n_positives(&all), 4);
+
+        // x is too broad
+        let gold = GoldStandard::from_annotation(&annotation, 2);
+        assert_eq!(gold.index("a"), None);
+    }
+
+    #[test]
+    fn test_curve_1() {
+        let mut edges = vec![(1., true), (2., false), (2., true), (3., false)];
+        let c = curve(&mut edges);
+        assert_eq!(c.len(), 3);
+        assert_eq!((c[1].edges, c[1].tp, c[1].fp), (3, 2, 1));
+
+        // 2 positives and 3 negatives in total, 1 negative not in the network
+        // points (0, 0.5), (1/3, 1), (2/3, 1), then (1, 1)
+        assert_abs_diff_eq!(auroc(&c, 2, 3), 1. - 1. / 12., epsilon = 1e-12);
+        // 0.5 * 1 + 0.5 * 2 / 3
+        assert_abs_diff_eq!(auprc(&c, 2), 5. / 6., epsilon = 1e-12);
+        assert_eq!(at_precision(&c, 0.6).unwrap().edges, 3);
+    }
+}
@@ -0,0 +1,268 @@
+use anyhow::{anyhow, Result};
+
+const BASES: [u8; 4] = *b"TCAG";
+
+/// NCBI translation tables: `(id, name, amino acids, starts)`, codons in TCAG order.
+/// In `starts`, `M` marks start codons and `*` codons that are stops only at the end of a
+/// gene (tables 27, 28 and 31).
+const TABLES: [(u32, &str, &str, &str); 25] = [
+    (
+        1,
+        "Standard",
+        "FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "---M------**--*----M---------------M----------------------------",
+    ),
+    (
+        2,
+        "Vertebrate Mitochondrial",
+        "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSS**VVVVAAAADDEEGGGG",
+        "----------**--------------------MMMM----------**---M------------",
+    ),
+    (
+        3,
+        "Yeast Mitochondrial",
+        "FFLLSSSSYY**CCWWTTTTPPPPHHQQRRRRIIMMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "----------**----------------------MM---------------M------------",
+    ),
+    (
+        4,
+        "Mold, Protozoan, and Coelenterate Mitochondrial and Mycoplasma/Spiroplasma",
+        "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "--MM------**-------M------------MMMM---------------M------------",
+    ),
+    (
+        5,
+        "Invertebrate Mitochondrial",
+        "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSSSVVVVAAAADDEEGGGG",
+        "---M------**--------------------MMMM---------------M------------",
+    ),
+    (
+        6,
+        "Ciliate, Dasycladacean and Hexamita Nuclear",
+        "FFLLSSSSYYQQCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "--------------*--------------------M----------------------------",
+    ),
+    (
+        9,
+        "Echinoderm and Flatworm Mitochondrial",
+        "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        "-----------------------------------M---------------M------------",
+    ),
+    (
+        10,
+        "Euplotid Nuclear",
+        "FFLLSSSSYY**CCCWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "----------**-----------------------M----------------------------",
+    ),
+    (
+        11,
+        "Bacterial, Archaeal and Plant Plastid",
+        "FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "---M------**--*----M------------MMMM---------------M------------",
+    ),
+    (
+        12,
+        "Alternative Yeast Nuclear",
+        "FFLLSSSSYY**CC*WLLLSPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "-------------------M---------------M----------------------------",
+    ),
+    (
+        13,
+        "Ascidian Mitochondrial",
+        "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSGGVVVVAAAADDEEGGGG",
+        "---M------------------------------MM---------------M------------",
+    ),
+    (
+        14,
+        "Alternative Flatworm Mitochondrial",
+        "FFLLSSSSYYY*CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        "-----------------------------------M----------------------------",
+    ),
+    (
+        16,
+        "Chlorophycean Mitochondrial",
+        "FFLLSSSSYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "-----------------------------------M----------------------------",
+    ),
+    (
+        21,
+        "Trematode Mitochondrial",
+        "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        "-----------------------------------M---------------M------------",
+    ),
+    (
+        22,
+        "Scenedesmus obliquus Mitochondrial",
+        "FFLLSS*SYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "-----------------------------------M----------------------------",
+    ),
+    (
+        23,
+        "Thraustochytrium Mitochondrial",
+        "FF*LSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "--------------------------------M--M---------------M------------",
+    ),
+    (
+        24,
+        "Rhabdopleuridae Mitochondrial",
+        "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSSKVVVVAAAADDEEGGGG",
+        "---M------**-------M---------------M---------------M------------",
+    ),
+    (
+        25,
+        "Candidate Division SR1 and Gracilibacteria",
+        "FFLLSSSSYY**CCGWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "---M-------------------------------M---------------M------------",
+    ),
+    (
+        26,
+        "Pachysolen tannophilus Nuclear",
+        "FFLLSSSSYY**CC*WLLLAPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "-------------------M---------------M----------------------------",
+    ),
+    (
+        27,
+        "Karyorelict Nuclear",
+        "FFLLSSSSYYQQCCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "--------------*--------------------M----------------------------",
+    ),
+    (
+        28,
+        "Condylostoma Nuclear",
+        "FFLLSSSSYYQQCCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "----------**--*--------------------M----------------------------",
+    ),
+    (
+        29,
+        "Mesodinium Nuclear",
+        "FFLLSSSSYYYYCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "--------------*--------------------M----------------------------",
+    ),
+    (
+        30,
+        "Peritrich Nuclear",
+        "FFLLSSSSYYEECC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "--------------*--------------------M----------------------------",
+    ),
+    (
+        31,
+        "Blastocrithidia Nuclear",
+        "FFLLSSSSYYEECCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "----------**-----------------------M----------------------------",
+    ),
+    (
+        33,
+        "Cephalodiscidae Mitochondrial",
+        "FFLLSSSSYYY*CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSSKVVVVAAAADDEEGGGG",
+        "---M-------*-------M---------------M---------------M------------",
+    ),
+];
+
+/// A genetic code: amino acid of every codon, stop and start codons.
+#[derive(Debug, Clone)]
+pub struct GeneticCode {
+    pub id: u32,
+    pub name: &'static str,
+    amino_acids: &'static [u8],
+    starts: &'static [u8],
+}
+
+impl Default for GeneticCode {
+    fn default() -> Self {
+        Self::from_ncbi(1).unwrap()
+    }
+}
+
+fn codon_index(codon: &[u8]) -> Option<usize> {
+    if codon.len() != 3 {
+        return None;
+    }
+    codon.iter().try_fold(0, |acc, b| {
+        BASES.iter().position(|x| x == b).map(|i| acc * 4 + i)
+    })
+}
+
+impl GeneticCode {
+    /// The NCBI translation table `id`.
+    pub fn from_ncbi(id: u32) -> Result<Self> {
+        let (_, name, amino_acids, starts) =
+            TABLES.iter().find(|t| t.0 == id).ok_or_else(|| {
+                anyhow!(
+                    "unknown genetic code {}, available: {:?}",
+                    id,
+                    TABLES.iter().map(|t| t.0).collect::<Vec<u32>>()
+                )
+            })?;
+        Ok(Self {
+            id,
+            name,
+            amino_acids: amino_acids.as_bytes(),
+            starts: starts.as_bytes(),
+        })
+    }
+
+    /// One letter amino acid of `codon`, `*` for stops, `None` for ambiguous codons.
+    pub fn amino_acid(&self, codon: &[u8]) -> Option<char> {
+        codon_index(codon).map(|i| self.amino_acids[i] as char)
+    }
+
+    /// A stop codon anywhere in the reading frame.
+    pub fn is_stop(&self, codon: &[u8]) -> bool {
+        self.amino_acid(codon) == Some('*')
+    }
+
+    /// A stop codon at the end of a gene, including context dependent stops.
+    pub fn is_terminal_stop(&self, codon: &[u8]) -> bool {
+        self.is_stop(codon) || codon_index(codon).is_some_and(|i| self.starts[i] == b'*')
+    }
+
+    pub fn is_start(&self, codon: &[u8]) -> bool {
+        codon_index(codon).is_some_and(|i| self.starts[i] == b'M')
+    }
+
+    /// Sense codons, sorted alphabetically.
+    pub fn sense_codons(&self) -> Vec<String> {
+        let mut codons: Vec<String> = (0..64)
+            .filter(|&i| self.amino_acids[i] != b'*')
+            .map(|i| {
+                [i / 16, i / 4 % 4, i % 4]
+                    .iter()
+                    .map(|&b| BASES[b] as char)
+                    .collect()
+            })
+            .collect();
+        codons.sort();
+        codons
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_genetic_code_1() {
+        let standard = GeneticCode::default();
+        assert_eq!(standard.sense_codons().len(), 61);
+        assert!(standard.is_stop(b"TGA"));
+        assert_eq!(standard.amino_acid(b"ATG"), Some('M'));
+        assert_eq!(standard.amino_acid(b"ANG"), None);
+        assert!(standard.is_start(b"ATG"));
+
+        let mito = GeneticCode::from_ncbi(2).unwrap();
+        assert_eq!(mito.amino_acid(b"TGA"), Some('W'));
+        assert!(mito.is_stop(b"AGA") && mito.is_stop(b"AGG"));
+        assert_eq!(mito.sense_codons().len(), 60);
+
+        let ciliate = GeneticCode::from_ncbi(6).unwrap();
+        assert_eq!(ciliate.amino_acid(b"TAA"), Some('Q'));
+        assert_eq!(ciliate.sense_codons().len(), 63);
+
+        // TGA is a stop only at the end of a gene
+        let karyorelict = GeneticCode::from_ncbi(27).unwrap();
+        assert!(!karyorelict.is_stop(b"TGA"));
+        assert!(karyorelict.is_terminal_stop(b"TGA"));
+
+        assert!(GeneticCode::from_ncbi(7).is_err());
+    }
+}
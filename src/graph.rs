@@ -33,12 +33,12 @@ where
     }
 }
 
-impl<T> ToString for Node<T>
+impl<T> Display for Node<T>
 where
     T: Clone + Copy,
 {
-    fn to_string(&self) -> String {
-        self.node_name.clone()
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.node_name)
     }
 }
 
@@ -70,11 +70,18 @@ where
         self.node_1
     }
 
-    #[allow(dead_code)]
     pub fn target(&self) -> usize {
         self.node_2
     }
 
+    pub fn corr(&self) -> f64 {
+        self.corr
+    }
+
+    pub fn rank(&self) -> T {
+        self.rank
+    }
+
     pub fn node_names<S: ToString>(&self, nodes: &[S]) -> (String, String) {
         (
             nodes[self.node_1].to_string(),
@@ -87,7 +94,7 @@ impl<T> Edge<T>
 where
     T: Clone + Copy + ToString,
 {
-    pub fn to_record<S: ToString>(&self, nodes: &[S]) -> io::CsvRecord {
+    pub fn to_record<S: ToString>(self, nodes: &[S]) -> io::CsvRecord {
         let (node_1_name, node_2_name) = self.node_names(nodes);
         io::CsvRecord::new(node_1_name, node_2_name, self.corr, self.rank.to_string())
     }
@@ -113,11 +120,10 @@ where
         }
     }
 
-    #[allow(dead_code)]
-    pub fn from_edges(nodes: &Vec<String>, edges: &Vec<Edge<T>>) -> Self {
+    pub fn from_edges(nodes: &[String], edges: &[Edge<T>]) -> Self {
         let mut g = Graph::new(nodes);
         for edge in edges.iter() {
-            g.push(edge.clone())
+            g.push(*edge)
         }
         g
     }
@@ -127,7 +133,7 @@ where
         self.nodes[query].push(edge);
     }
 
-    fn size(&self) -> usize {
+    pub fn size(&self) -> usize {
         self.nodes.len()
     }
 
@@ -144,6 +150,105 @@ where
 
         edges
     }
+
+    pub fn node_names(&self) -> Vec<String> {
+        self.nodes.iter().map(|n| n.to_string()).collect()
+    }
+
+    /// Undirected CSR view of the graph with unit weights.
+    pub fn to_csr(&self) -> Csr {
+        self.to_weighted_csr(|_| 1.)
+    }
+
+    /// Undirected CSR view of the graph, weighting each edge with `weight`.
+    pub fn to_weighted_csr<F: Fn(&Edge<T>) -> f64>(&self, weight: F) -> Csr {
+        let edges: Vec<(usize, usize, f64)> = self
+            .nodes()
+            .iter()
+            .flat_map(|n| n.edges.iter())
+            .map(|e| (e.query(), e.target(), weight(e)))
+            .collect();
+        Csr::from_weighted_pairs(self.size(), &edges)
+    }
+}
+
+/// Edge of a gene network on a node index: `(gene_1, gene_2, corr, rank)`.
+pub type RankedEdge = (usize, usize, f64, f64);
+
+/// An undirected pair of nodes as `(min, max)`, to match edges listed in either orientation.
+pub fn pair_key(a: usize, b: usize) -> (usize, usize) {
+    (std::cmp::min(a, b), std::cmp::max(a, b))
+}
+
+/// Compressed sparse row adjacency of an undirected graph.
+/// Every edge is stored twice, once from each end.
+#[derive(Debug, Clone)]
+pub struct Csr {
+    offsets: Vec<usize>,
+    neighbors: Vec<usize>,
+    weights: Vec<f64>,
+}
+
+impl Csr {
+    /// Build from `(node_1, node_2)` pairs with unit weights.
+    pub fn from_pairs(size: usize, pairs: &[(usize, usize)]) -> Self {
+        let edges: Vec<(usize, usize, f64)> = pairs.iter().map(|&(a, b)| (a, b, 1.)).collect();
+        Self::from_weighted_pairs(size, &edges)
+    }
+
+    /// Build from `(node_1, node_2, weight)` triples. Each pair should appear once.
+    pub fn from_weighted_pairs(size: usize, edges: &[(usize, usize, f64)]) -> Self {
+        let mut offsets = vec![0; size + 1];
+        for &(a, b, _) in edges.iter() {
+            offsets[a + 1] += 1;
+            offsets[b + 1] += 1;
+        }
+        for i in 0..size {
+            offsets[i + 1] += offsets[i];
+        }
+
+        let mut cursor = offsets.clone();
+        let mut neighbors = vec![0; offsets[size]];
+        let mut weights = vec![0.; offsets[size]];
+        for &(a, b, w) in edges.iter() {
+            for (x, y) in [(a, b), (b, a)] {
+                neighbors[cursor[x]] = y;
+                weights[cursor[x]] = w;
+                cursor[x] += 1;
+            }
+        }
+
+        Self {
+            offsets,
+            neighbors,
+            weights,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    pub fn n_edges(&self) -> usize {
+        self.neighbors.len() / 2
+    }
+
+    pub fn degree(&self, i: usize) -> usize {
+        self.offsets[i + 1] - self.offsets[i]
+    }
+
+    pub fn neighbors(&self, i: usize) -> &[usize] {
+        &self.neighbors[self.offsets[i]..self.offsets[i + 1]]
+    }
+
+    pub fn weights(&self, i: usize) -> &[f64] {
+        &self.weights[self.offsets[i]..self.offsets[i + 1]]
+    }
+
+    /// Weighted degree of node `i`.
+    pub fn strength(&self, i: usize) -> f64 {
+        self.weights(i).iter().sum()
+    }
 }
 
 impl<T> Graph<T>
@@ -0,0 +1,89 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use csv::WriterBuilder;
+use ordered_float::OrderedFloat;
+
+use crate::centrality;
+use crate::io;
+use crate::rank;
+use crate::{Centrality, Weight};
+
+#[derive(Debug, Serialize)]
+struct CentralityRecord {
+    gene: String,
+    degree: usize,
+    weighted_degree: f64,
+    betweenness: f64,
+    closeness: f64,
+    eigenvector: f64,
+    pagerank: f64,
+}
+
+impl CentralityRecord {
+    fn key(&self, sort_by: &Centrality) -> OrderedFloat<f64> {
+        OrderedFloat::from(match sort_by {
+            Centrality::Degree => self.degree as f64,
+            Centrality::WeightedDegree => self.weighted_degree,
+            Centrality::Betweenness => self.betweenness,
+            Centrality::Closeness => self.closeness,
+            Centrality::Eigenvector => self.eigenvector,
+            Centrality::PageRank => self.pagerank,
+        })
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn parse_args(
+    input: &Path,
+    output: Option<&PathBuf>,
+    rank_column: Option<&String>,
+    weight: &Weight,
+    decay: &f64,
+    samples: Option<&usize>,
+    seed: &u64,
+    sort_by: &Centrality,
+    top: Option<&usize>,
+) -> Result<()> {
+    info!("--- start read {:?} ---", input);
+    let g = io::read_graph(input, rank_column.map(|x| x.as_str()))?;
+    let csr = g.to_weighted_csr(|e| rank::to_weight(weight, e.corr(), e.rank(), *decay));
+    let n = csr.size();
+
+    info!("calculate betweenness (samples: {:?})...", samples);
+    let betweenness = centrality::betweenness(&csr, samples.cloned(), *seed);
+    info!("calculate closeness...");
+    let closeness = centrality::closeness(&csr);
+    info!("calculate eigenvector centrality...");
+    let eigenvector = centrality::eigenvector(&csr, 1000, 1e-6);
+    info!("calculate pagerank...");
+    let pagerank = centrality::pagerank(&csr, 0.85, 1000, 1e-10);
+
+    let mut records: Vec<CentralityRecord> = g
+        .node_names()
+        .into_iter()
+        .enumerate()
+        .map(|(i, gene)| CentralityRecord {
+            gene,
+            degree: csr.degree(i),
+            weighted_degree: csr.strength(i),
+            betweenness: betweenness[i],
+            closeness: closeness[i],
+            eigenvector: eigenvector[i],
+            pagerank: pagerank[i],
+        })
+        .collect();
+    records.sort_by_key(|r| std::cmp::Reverse(r.key(sort_by)));
+
+    let default_path = PathBuf::from("centrality.tsv");
+    let out_path = output.unwrap_or(&default_path);
+    let mut wtr = WriterBuilder::new().delimiter(b'\t').from_path(out_path)?;
+    for r in records.iter().take(*top.unwrap_or(&n)) {
+        wtr.serialize(r)?;
+    }
+    wtr.flush()?;
+
+    info!("Finish!");
+
+    Ok(())
+}
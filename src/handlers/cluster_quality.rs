@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use csv::WriterBuilder;
+
+use crate::community;
+use crate::io;
+use crate::math;
+use crate::quality;
+use crate::rank;
+use crate::Weight;
+
+#[derive(Debug, Serialize)]
+struct ClusterRecord<'a> {
+    cluster: &'a str,
+    size: usize,
+    internal_edges: usize,
+    boundary_edges: usize,
+    density: f64,
+    conductance: f64,
+    modularity: f64,
+    mean_rank_within: f64,
+    mean_rank_across: f64,
+    silhouette: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct SummaryRecord {
+    clusters: usize,
+    clustered_genes: usize,
+    unclustered_genes: usize,
+    modularity: f64,
+    coverage: f64,
+    mean_conductance: f64,
+    mean_density: f64,
+    mean_rank_within: f64,
+    mean_rank_across: f64,
+    silhouette: f64,
+}
+
+pub fn parse_args(
+    in_graph: &Path,
+    clusters: &Path,
+    output: Option<&PathBuf>,
+    rank_column: Option<&String>,
+    weight: &Weight,
+    decay: &f64,
+) -> Result<()> {
+    info!("--- start read {:?} ---", in_graph);
+    let g = io::read_graph(in_graph, rank_column.map(|x| x.as_str()))?;
+    let genes = g.node_names();
+    let gene_index: HashMap<&str, usize> = genes
+        .iter()
+        .enumerate()
+        .map(|(i, x)| (x.as_str(), i))
+        .collect();
+
+    let mut names: Vec<String> = vec![];
+    let mut name_index: HashMap<String, usize> = HashMap::new();
+    let mut labels: Vec<Option<usize>> = vec![None; genes.len()];
+    let mut missing = 0;
+    for (gene, cluster) in io::read_cluster_table(clusters)? {
+        let i = match gene_index.get(gene.as_str()) {
+            Some(&i) => i,
+            None => {
+                missing += 1;
+                continue;
+            }
+        };
+        let c = *name_index.entry(cluster.clone()).or_insert_with(|| {
+            names.push(cluster);
+            names.len() - 1
+        });
+        labels[i] = Some(c);
+    }
+    if names.is_empty() {
+        return Err(anyhow!("no clustered gene is found in {:?}", in_graph));
+    }
+    if missing > 0 {
+        warn!("{} clustered genes are not in the network", missing);
+    }
+
+    let csr = g.to_weighted_csr(|e| rank::to_weight(weight, e.corr(), e.rank(), *decay));
+    let corr = g.to_weighted_csr(|e| e.corr());
+    let ranks = g.to_weighted_csr(|e| e.rank());
+    let res = quality::cluster_quality(&csr, &corr, &ranks, &labels, names.len());
+
+    let default_path = PathBuf::from("cluster_quality");
+    let prefix = output.unwrap_or(&default_path);
+    let mut wtr = WriterBuilder::new()
+        .delimiter(b'\t')
+        .from_path(prefix.with_extension("tsv"))?;
+    for (name, q) in names.iter().zip(res.iter()) {
+        wtr.serialize(ClusterRecord {
+            cluster: name,
+            size: q.size,
+            internal_edges: q.internal_edges,
+            boundary_edges: q.boundary_edges,
+            density: q.density,
+            conductance: q.conductance,
+            modularity: q.modularity,
+            mean_rank_within: q.mean_rank_within,
+            mean_rank_across: q.mean_rank_across,
+            silhouette: q.silhouette,
+        })?;
+    }
+    wtr.flush()?;
+
+    // unclustered genes are singletons for the global modularity
+    let mut next = names.len();
+    let full_labels: Vec<usize> = labels
+        .iter()
+        .map(|l| {
+            l.unwrap_or_else(|| {
+                next += 1;
+                next - 1
+            })
+        })
+        .collect();
+
+    // global means are over edges and genes, not clusters
+    let (mut within, mut across) = ((0., 0), (0., 0));
+    for v in 0..ranks.size() {
+        for (&u, r) in ranks.neighbors(v).iter().zip(ranks.weights(v)) {
+            if u < v {
+                continue;
+            }
+            let acc = if labels[u].is_some() && labels[u] == labels[v] {
+                &mut within
+            } else {
+                &mut across
+            };
+            acc.0 += r;
+            acc.1 += 1;
+        }
+    }
+    let silhouette: Vec<f64> = quality::silhouette(&corr, &labels, names.len())
+        .into_iter()
+        .flatten()
+        .collect();
+    let clustered = labels.iter().filter(|x| x.is_some()).count();
+
+    let summary = SummaryRecord {
+        clusters: names.len(),
+        clustered_genes: clustered,
+        unclustered_genes: genes.len() - clustered,
+        modularity: community::modularity(&csr, &full_labels, 1.),
+        coverage: within.1 as f64 / (within.1 + across.1).max(1) as f64,
+        mean_conductance: math::mean(&res.iter().map(|q| q.conductance).collect::<Vec<f64>>()),
+        mean_density: math::mean(&res.iter().map(|q| q.density).collect::<Vec<f64>>()),
+        mean_rank_within: within.0 / within.1 as f64,
+        mean_rank_across: across.0 / across.1 as f64,
+        silhouette: math::mean(&silhouette),
+    };
+    info!("{:?}", summary);
+
+    let mut wtr = WriterBuilder::new()
+        .delimiter(b'\t')
+        .from_path(prefix.with_extension("summary.tsv"))?;
+    wtr.serialize(summary)?;
+    wtr.flush()?;
+
+    info!("Finish!");
+
+    Ok(())
+}
@@ -1,10 +1,54 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
+
+use crate::community;
+use crate::io;
+use crate::mcl::{self, MclParams};
+use crate::rank;
+use crate::{Clustering, Weight};
+
+#[allow(clippy::too_many_arguments)]
+pub fn parse_args(
+    in_graph: &Path,
+    output: Option<&PathBuf>,
+    method: &Clustering,
+    rank_column: Option<&String>,
+    weight: &Weight,
+    decay: &f64,
+    resolution: &f64,
+    seed: &u64,
+    mcl_params: &MclParams,
+) -> Result<()> {
+    info!("--- start read {:?} ---", in_graph);
+    let g = io::read_graph(in_graph, rank_column.map(|x| x.as_str()))?;
+    let csr = g.to_weighted_csr(|e| rank::to_weight(weight, e.corr(), e.rank(), *decay));
+
+    info!(
+        "Method: {}, weight: {}, resolution: {}, seed: {}",
+        method, weight, resolution, seed
+    );
+    let labels = match method {
+        Clustering::HCCA => bail!("HCCA is not implemented yet"),
+        Clustering::Louvain => community::louvain(&csr, *resolution, *seed),
+        Clustering::Leiden => community::leiden(&csr, *resolution, *seed),
+        Clustering::MCL => {
+            info!("{:?}", mcl_params);
+            mcl::mcl(&csr, mcl_params)
+        }
+    };
+
+    info!(
+        "{} clusters, modularity: {}",
+        labels.iter().max().map_or(0, |x| x + 1),
+        community::modularity(&csr, &labels, *resolution)
+    );
+
+    let default_path = PathBuf::from("clusters.tsv");
+    let out_path = output.unwrap_or(&default_path);
+    io::write_cluster_table(out_path, &g.node_names(), &labels)?;
+
+    info!("Finish!");
 
-#[allow(dead_code)]
-#[allow(unused_variables)]
-pub fn parse_args(in_graph: &Path, rank_cutoff: Option<&f64>) -> Result<()> {
-    error!("Not implemented yet");
     Ok(())
 }
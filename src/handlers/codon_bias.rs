@@ -0,0 +1,128 @@
+use anyhow::{bail, Result};
+use csv::WriterBuilder;
+use std::path::{Path, PathBuf};
+
+use crate::codon;
+use crate::io;
+use crate::math;
+
+#[derive(Debug, Serialize)]
+struct BiasRecord<'a> {
+    gene: &'a str,
+    length: usize,
+    codons: usize,
+    gc: f64,
+    gc3s: Option<f64>,
+    enc: Option<f64>,
+    cai: Option<f64>,
+}
+
+fn gc_content(seq: &str) -> f64 {
+    let gc = seq.bytes().filter(|b| b"GC".contains(b)).count();
+    let acgt = seq.bytes().filter(|b| b"ACGT".contains(b)).count();
+    gc as f64 / acgt as f64
+}
+
+fn print_mean(name: &str, values: &[Option<f64>]) {
+    let values: Vec<f64> = values.iter().flatten().cloned().collect();
+    println!(
+        "{}: {} (genes: {})",
+        name,
+        math::mean(&values),
+        values.len()
+    );
+}
+
+pub fn parse_args(
+    input_fasta: &Path,
+    reference: Option<&PathBuf>,
+    cds_options: &codon::CdsOptions,
+    cds_report: Option<&PathBuf>,
+    output: Option<&PathBuf>,
+    rscu_output: Option<&PathBuf>,
+) -> Result<()> {
+    info!("start read fasta...");
+    let (index, seqs) = io::read_cds(input_fasta, cds_options, cds_report)?;
+    let code = &cds_options.genetic_code;
+    let codon_arr = codon::make_codon_arr(&seqs, code)?;
+
+    let w = match reference {
+        Some(reference) => {
+            let reference = io::read_gene_list(&reference)?;
+            let mut pooled = vec![0; codon_arr.ncols()];
+            let mut found = 0;
+            for (gene, counts) in index.iter().zip(codon_arr.outer_iter()) {
+                if reference.contains(gene) {
+                    found += 1;
+                    pooled.iter_mut().zip(counts).for_each(|(p, c)| *p += c);
+                }
+            }
+            if found == 0 {
+                bail!("no reference gene found in {:?}", input_fasta);
+            }
+            if found < reference.len() {
+                warn!(
+                    "{} of {} reference genes not found",
+                    reference.len() - found,
+                    reference.len()
+                );
+            }
+            info!("CAI reference: {} genes", found);
+            Some(codon::relative_adaptiveness(&pooled, code))
+        }
+        None => None,
+    };
+
+    let default_path = PathBuf::from("codon_bias.tsv");
+    let mut records = vec![];
+    for ((gene, seq), counts) in index.iter().zip(seqs.iter()).zip(codon_arr.outer_iter()) {
+        let counts = counts.to_vec();
+        records.push(BiasRecord {
+            gene,
+            length: seq.len(),
+            codons: counts.iter().sum(),
+            gc: gc_content(seq),
+            gc3s: codon::gc3s(&counts, code),
+            enc: codon::enc(&counts, code),
+            cai: w.as_ref().and_then(|w| codon::cai(&counts, w, code)),
+        });
+    }
+    io::write_tsv(output.unwrap_or(&default_path), &records)?;
+
+    println!("Genes: {}", records.len());
+    print_mean(
+        "Mean GC3s",
+        &records.iter().map(|r| r.gc3s).collect::<Vec<_>>(),
+    );
+    print_mean(
+        "Mean ENC",
+        &records.iter().map(|r| r.enc).collect::<Vec<_>>(),
+    );
+    if w.is_some() {
+        print_mean(
+            "Mean CAI",
+            &records.iter().map(|r| r.cai).collect::<Vec<_>>(),
+        );
+    }
+
+    if let Some(rscu_output) = rscu_output {
+        let mut wtr = WriterBuilder::new()
+            .delimiter(b'\t')
+            .from_path(rscu_output)?;
+        let mut header = vec!["gene".to_string()];
+        header.extend(code.sense_codons());
+        wtr.write_record(&header)?;
+        for (gene, counts) in index.iter().zip(codon_arr.outer_iter()) {
+            let mut record = vec![gene.clone()];
+            record.extend(
+                codon::rscu(&counts.to_vec(), code)
+                    .iter()
+                    .map(|x| x.to_string()),
+            );
+            wtr.write_record(&record)?;
+        }
+        wtr.flush()?;
+    }
+
+    Ok(())
+}
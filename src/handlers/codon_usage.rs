@@ -1,90 +1,327 @@
-use anyhow::Result;
-use csv::Reader;
-use itertools::Itertools;
+use anyhow::{bail, Result};
+use csv::{Reader, WriterBuilder};
+use ndarray::Array2;
+use ndarray_stats::*;
 use ordered_float::OrderedFloat;
-use rayon::prelude::*;
-use std::sync::{Arc, Mutex};
-use std::{collections::HashMap, path::Path};
+use rand::{rngs::StdRng, SeedableRng};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use crate::codon;
 use crate::io;
 use crate::math;
+use crate::null;
 use crate::rank;
 use crate::similarity;
+use crate::CodonProfile;
+use crate::NullModel;
+use crate::RankMetric;
 
-pub fn parse_args(input_graph: &Path, input_fasta: &Path, percent: &f64) -> Result<()> {
-    info!("start caluculate coden score...");
+#[derive(Debug, Serialize)]
+struct ScoreRecord<'a> {
+    gene: &'a str,
+    metric: String,
+    score: f64,
+    neighbors: usize,
+    k: usize,
+}
+
+/// Quantiles, mean and a text histogram of the per-gene scores.
+fn print_distribution(values: &[f64], bins: usize) {
+    println!("Genes: {}", values.len());
+    println!("Mean: {}", math::mean(values));
+    for (name, q) in [
+        ("Min", 0.),
+        ("Q1", 0.25),
+        ("Median", 0.5),
+        ("Q3", 0.75),
+        ("Max", 1.),
+    ]
+    .iter()
+    {
+        println!("{}: {}", name, math::quantile(values, *q));
+    }
+
+    let counts = math::histogram(values, bins, 0., 1.);
+    let max = counts.iter().cloned().max().unwrap_or(0).max(1);
+    println!("Histogram:");
+    for (b, count) in counts.iter().enumerate() {
+        println!(
+            "[{:.2}, {:.2}{} {:>8} {}",
+            b as f64 / bins as f64,
+            (b + 1) as f64 / bins as f64,
+            if b + 1 == bins { "]" } else { ")" },
+            count,
+            "#".repeat(count * 50 / max)
+        );
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct CurveRecord {
+    metric: String,
+    k: usize,
+    genes: usize,
+    mean: f64,
+    median: f64,
+    null_mean: Option<f64>,
+    null_sd: Option<f64>,
+    z: Option<f64>,
+    pvalue: Option<f64>,
+}
 
-    info!("start read fasta...");
-    let (index, seqs) = io::read_fasta(input_fasta)?;
+/// Genes and their rank matrix of similarity in the reference signal: codon usage of the
+/// FASTA, or correlation of the rows of a feature matrix.
+fn reference_rank(
+    input_fasta: Option<&PathBuf>,
+    features: Option<&PathBuf>,
+    profile: &CodonProfile,
+    cds_options: &codon::CdsOptions,
+    cds_report: Option<&PathBuf>,
+) -> Result<(Vec<String>, Array2<usize>)> {
+    match (input_fasta, features) {
+        (Some(input_fasta), _) => {
+            info!("start read fasta...");
+            let (index, seqs) = io::read_cds(input_fasta, cds_options, cds_report)?;
+            info!("start construct codon rank matrix ({})", profile);
+            let codon_rank = codon::make_codon_rank(&seqs, &cds_options.genetic_code, profile)?;
+            Ok((index, codon_rank))
+        }
+        (None, Some(features)) => {
+            info!("start read features {:?}...", features);
+            let mut index = vec![];
+            let arr = io::read_exp_csv(features, &mut index)?;
+            let corr = arr.pearson_correlation()?;
+            let feature_rank = rank::construct_rank_matrix_multithreading(&corr, index.len())?;
+            Ok((index, feature_rank))
+        }
+        (None, None) => bail!("either a fasta or a feature matrix is needed"),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn parse_args(
+    input_graph: &Path,
+    input_fasta: Option<&PathBuf>,
+    features: Option<&PathBuf>,
+    percent: &f64,
+    ks: &[usize],
+    curve: Option<&PathBuf>,
+    metric: &RankMetric,
+    rbo_p: &f64,
+    profile: &CodonProfile,
+    cds_options: &codon::CdsOptions,
+    cds_report: Option<&PathBuf>,
+    output: Option<&PathBuf>,
+    bins: &usize,
+    permutations: &usize,
+    null_model: &NullModel,
+    swaps: &usize,
+    seed: &u64,
+) -> Result<()> {
+    if *bins == 0 {
+        bail!("--bins must be at least 1");
+    }
+    info!("start caluculate coden score...");
 
-    info!("start construct codon rank matrix");
-    // codon arr: convert to rank
-    let codon_rank = codon::make_codon_rank(&seqs)?;
+    let (index, reference_rank) =
+        reference_rank(input_fasta, features, profile, cds_options, cds_report)?;
+    let (source, score_name) = match input_fasta {
+        Some(_) => ("fasta", "Codon Score"),
+        None => ("features", "Feature Score"),
+    };
 
-    // graph: sort by rank
+    // graph: gene index and edges with rank
     info!("start to read graph...");
     let mut rdr = Reader::from_path(input_graph)?;
-    let mut map: HashMap<String, Vec<(String, OrderedFloat<f64>)>> = HashMap::new();
+    let mut nodes: Vec<String> = vec![];
+    let mut node_index: HashMap<String, usize> = HashMap::new();
+    let mut edges: Vec<(usize, usize, f64)> = vec![];
 
     for _r in rdr.deserialize() {
         let r: io::CsvRecord = _r?;
         let (gene_1, gene_2) = r.genes();
-        let rank = OrderedFloat::from(r.rank::<f64>());
-        map.entry(gene_1.clone())
-            .or_insert_with(Vec::new)
-            .push((gene_2.clone(), rank));
-        map.entry(gene_2)
-            .or_insert_with(Vec::new)
-            .push((gene_1, rank));
+        let mut intern = |name: String| {
+            let n = nodes.len();
+            *node_index.entry(name.clone()).or_insert_with(|| {
+                nodes.push(name);
+                n
+            })
+        };
+        let (i, j) = (intern(gene_1), intern(gene_2));
+        edges.push((i, j, r.rank::<f64>()));
     }
 
-    let corr_index_cnt = map.keys().count();
-    let fasta_index_cnt = index.len();
+    io::map_network_ids(&mut nodes)?;
 
-    let k = (std::cmp::min(corr_index_cnt, fasta_index_cnt) as f64 * percent) as usize;
+    let corr_index_cnt = nodes.len();
+    let reference_index_cnt = index.len();
+
+    let ks: Vec<usize> = if ks.is_empty() {
+        vec![(std::cmp::min(corr_index_cnt, reference_index_cnt) as f64 * percent) as usize]
+    } else {
+        ks.to_vec()
+    };
+    if ks.contains(&0) {
+        bail!("k must be positive");
+    }
+    let k_max = ks.iter().cloned().max().unwrap_or(0);
+    if k_max >= reference_index_cnt {
+        bail!(
+            "k = {} is not smaller than the {} genes in the {}",
+            k_max,
+            reference_index_cnt,
+            source
+        );
+    }
 
     info!(
-        "In rank based graph, there is {} genes and in fasta, there is {} genes",
-        corr_index_cnt, fasta_index_cnt
+        "In rank based graph, there is {} genes and in {}, there is {} genes",
+        corr_index_cnt, source, reference_index_cnt
     );
-    info!("This tool calculate top_k as {}", k);
+    info!("This tool calculate top_k as {:?}", ks);
 
     info!("start calculate cosmix score...");
 
-    // calc cosmix values
-    let tmap = Arc::new(Mutex::new(map));
-    let cosmix_values: Vec<f64> = (0..index.len())
-        .into_par_iter()
-        .filter_map(|i| {
-            let m = Arc::clone(&tmap);
-            if let Some(corr_ranked) = m.lock().unwrap().get_mut(&index[i]) {
-                corr_ranked.sort_by(|a, b| a.1.cmp(&b.1));
-                let corr_ranked_vec = corr_ranked.iter().map(|x| x.0.to_owned()).collect_vec();
-                let codon_ranked_vec: Vec<String> =
-                    rank::get_index_sorted_by_rank(&codon_rank, i, &index);
-
-                return Some(similarity::cosmix(&corr_ranked_vec, &codon_ranked_vec, k));
-            }
-            None
-        })
+    // calc per-gene scores
+    let reference_pos: HashMap<&str, usize> = index
+        .iter()
+        .enumerate()
+        .map(|(i, x)| (x.as_str(), i))
+        .collect();
+    let labels: Vec<Option<usize>> = nodes
+        .iter()
+        .map(|x| reference_pos.get(x.as_str()).cloned())
+        .collect();
+    let matched = labels.iter().flatten().count();
+    info!(
+        "{} network genes matched in {}, {} unmatched",
+        matched,
+        source,
+        nodes.len() - matched
+    );
+    if matched == 0 {
+        bail!(
+            "no network gene is found in the {}, map ids with --id_map or --id_strip",
+            source
+        );
+    }
+    // top lists are sorted, so the top k_max serves every k
+    let reference_top: Vec<Vec<usize>> = (0..index.len())
+        .map(|i| rank::top_k_by_rank(&reference_rank, i, k_max))
         .collect();
+    let adj = similarity::ranked_adjacency(nodes.len(), &edges);
 
-    // for i in 0..index.len() {
-    //     // let gene_id = index[i]
-    //     let corr_ranked_vec: Vec<String> = match sort_corr_by_rank(&mut map, &index[i]) {
-    //         Some(v) => v,
-    //         None => continue,
-    //     };
-    //     let codon_ranked_vec: Vec<String> = rank::get_index_sorted_by_rank(&codon_rank, i, &index);
+    let mut wtr = match output {
+        Some(output) => Some(WriterBuilder::new().delimiter(b'\t').from_path(output)?),
+        None => None,
+    };
+    let mut curve_records = vec![];
+    let mut rng = StdRng::seed_from_u64(*seed);
 
-    //     cosmix_values.push(similarity::cosmix(&corr_ranked_vec, &codon_ranked_vec, k));
-    // }
+    for &k in ks.iter() {
+        let per_gene =
+            similarity::neighbor_scores(&adj, &labels, &reference_top, k, metric, *rbo_p);
+        let values: Vec<f64> = per_gene.iter().flatten().cloned().collect();
 
-    info!("caluculation is done!");
+        info!("caluculation is done! (k = {})", k);
 
-    // print median of cosmix values
-    println!("Codon Score: {}", math::median(&cosmix_values));
+        if let Some(wtr) = wtr.as_mut() {
+            for (i, value) in per_gene.iter().enumerate() {
+                if let Some(value) = value {
+                    wtr.serialize(ScoreRecord {
+                        gene: &nodes[i],
+                        metric: metric.to_string(),
+                        score: *value,
+                        neighbors: adj[i].len(),
+                        k,
+                    })?;
+                }
+            }
+        }
+
+        // print median of the scores
+        if ks.len() > 1 {
+            println!("k: {}", k);
+        }
+        let observed = math::median(&values);
+        match metric {
+            RankMetric::COSMIX => println!("{}: {}", score_name, observed),
+            _ => println!("{} ({}): {}", score_name, metric, observed),
+        }
+        print_distribution(&values, *bins);
+
+        let mut record = CurveRecord {
+            metric: metric.to_string(),
+            k,
+            genes: values.len(),
+            mean: math::mean(&values),
+            median: observed,
+            null_mean: None,
+            null_sd: None,
+            z: None,
+            pvalue: None,
+        };
+
+        if *permutations > 0 {
+            info!(
+                "start {} {} null model...",
+                permutations,
+                null_model.to_string().to_lowercase()
+            );
+            let null: Vec<f64> = (0..*permutations)
+                .map(|_| {
+                    let values: Vec<Option<f64>> = match null_model {
+                        NullModel::Permutation => {
+                            let labels = null::permute_labels(&labels, &mut rng);
+                            similarity::neighbor_scores(
+                                &adj,
+                                &labels,
+                                &reference_top,
+                                k,
+                                metric,
+                                *rbo_p,
+                            )
+                        }
+                        NullModel::Rewiring => {
+                            let mut rewired = edges.clone();
+                            null::rewire(&mut rewired, swaps * edges.len(), &mut rng);
+                            let adj = similarity::ranked_adjacency(nodes.len(), &rewired);
+                            similarity::neighbor_scores(
+                                &adj,
+                                &labels,
+                                &reference_top,
+                                k,
+                                metric,
+                                *rbo_p,
+                            )
+                        }
+                    };
+                    math::median(&values.into_iter().flatten().collect::<Vec<f64>>())
+                })
+                .collect();
+
+            let (mean, sd, z, p) = null::summarize(observed, &null);
+            println!("Null Mean: {}", mean);
+            println!("Null SD: {}", sd);
+            println!("Z-score: {}", z);
+            println!("Empirical p-value: {}", p);
+            record.null_mean = Some(mean);
+            record.null_sd = Some(sd);
+            record.z = Some(z);
+            record.pvalue = Some(p);
+        }
+        curve_records.push(record);
+    }
+
+    if let Some(wtr) = wtr.as_mut() {
+        wtr.flush()?;
+    }
+    if let Some(curve) = curve {
+        io::write_tsv(curve, &curve_records)?;
+    }
 
     Ok(())
 }
@@ -94,12 +331,9 @@ fn sort_corr_by_rank(
     map: &mut HashMap<String, Vec<(String, OrderedFloat<f64>)>>,
     key: &str,
 ) -> Option<Vec<String>> {
-    let corr_ranked = match map.get_mut(key) {
-        Some(v) => v,
-        None => return None,
-    };
+    let corr_ranked = map.get_mut(key)?;
 
-    corr_ranked.sort_by(|a, b| a.1.cmp(&b.1));
+    corr_ranked.sort_by_key(|a| a.1);
 
     Some(corr_ranked.iter().map(|x| x.0.to_owned()).collect())
 }
@@ -129,6 +363,6 @@ mod test {
                 .collect::<Vec<String>>()
         );
 
-        assert_eq!(sort_corr_by_rank(&mut map, &"gene_2".to_string()), None,);
+        assert_eq!(sort_corr_by_rank(&mut map, "gene_2"), None,);
     }
 }
@@ -0,0 +1,126 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use ordered_float::OrderedFloat;
+
+use crate::compare;
+use crate::graph::RankedEdge;
+use crate::io;
+use crate::math;
+use crate::similarity;
+use crate::RankMetric;
+
+#[derive(Debug, Serialize)]
+struct GeneRecord<'a> {
+    gene: &'a str,
+    /// `both`, or `only_a` / `only_b` for genes of one network, which are not ranked
+    status: &'static str,
+    degree_a: usize,
+    degree_b: usize,
+    shared_neighbors: usize,
+    jaccard: f64,
+    metric: String,
+    score: f64,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn parse_args(
+    network_a: &Path,
+    network_b: &Path,
+    rank_column: Option<&String>,
+    k: &usize,
+    metric: &RankMetric,
+    rbo_p: &f64,
+    top: &usize,
+    output: Option<&PathBuf>,
+) -> Result<()> {
+    let rank_column = rank_column.map(|x| x.as_str());
+    let mut genes = io::GeneIndex::default();
+    info!("read {:?}", network_a);
+    let (nodes_a, a) = io::read_graph_shared(network_a, rank_column, &mut genes)?;
+    info!("read {:?}", network_b);
+    let (nodes_b, b) = io::read_graph_shared(network_b, rank_column, &mut genes)?;
+    let (n_genes_a, n_genes_b) = (nodes_a.len(), nodes_b.len());
+    let genes = genes.names;
+    let mut in_a = vec![false; genes.len()];
+    let mut in_b = vec![false; genes.len()];
+    nodes_a.iter().for_each(|&i| in_a[i] = true);
+    nodes_b.iter().for_each(|&i| in_b[i] = true);
+    let status: Vec<&str> = (0..genes.len())
+        .map(|i| match (in_a[i], in_b[i]) {
+            (true, true) => "both",
+            (true, false) => "only_a",
+            _ => "only_b",
+        })
+        .collect();
+
+    let overlap = compare::edge_overlap(&a, &b);
+
+    let ranked = |edges: &[RankedEdge]| {
+        let triples: Vec<(usize, usize, f64)> =
+            edges.iter().map(|&(i, j, _, rank)| (i, j, rank)).collect();
+        similarity::ranked_adjacency(genes.len(), &triples)
+    };
+    let change = compare::neighborhood_change(&ranked(&a), &ranked(&b), *k, metric, *rbo_p);
+
+    // genes of one network score 0 and would hide the changes of shared genes, so only
+    // shared genes are ranked; the others follow
+    let (mut order, mut one_network): (Vec<usize>, Vec<usize>) =
+        (0..genes.len()).partition(|&i| status[i] == "both");
+    order.sort_by_key(|&i| (OrderedFloat::from(change[i].score), genes[i].clone()));
+    let n_shared = order.len();
+    one_network.sort_by_key(|&i| (status[i], genes[i].clone()));
+    order.extend(one_network);
+
+    let default_path = PathBuf::from("compare");
+    let prefix = output.unwrap_or(&default_path);
+    io::write_tsv(
+        prefix.with_extension("summary.tsv"),
+        std::slice::from_ref(&overlap),
+    )?;
+    let records: Vec<GeneRecord> = order
+        .iter()
+        .map(|&i| GeneRecord {
+            gene: &genes[i],
+            status: status[i],
+            degree_a: change[i].degree_a,
+            degree_b: change[i].degree_b,
+            shared_neighbors: change[i].shared_neighbors,
+            jaccard: change[i].jaccard,
+            metric: metric.to_string(),
+            score: change[i].score,
+        })
+        .collect();
+    io::write_tsv(prefix.with_extension("genes.tsv"), &records)?;
+
+    println!("Genes: {} / {}", n_genes_a, n_genes_b);
+    println!("Shared Genes: {}", n_shared);
+    println!(
+        "Unique Genes: {} / {}",
+        n_genes_a - n_shared,
+        n_genes_b - n_shared
+    );
+    println!("Edges: {} / {}", overlap.edges_a, overlap.edges_b);
+    println!("Shared Edges: {}", overlap.shared);
+    println!("Unique Edges: {} / {}", overlap.only_a, overlap.only_b);
+    println!("Total Edges: {}", overlap.union);
+    println!("Edge Jaccard: {}", overlap.jaccard);
+    println!("Rank Spearman (shared edges): {}", overlap.rank_spearman);
+    println!("Corr Pearson (shared edges): {}", overlap.corr_pearson);
+    let scores: Vec<f64> = order[..n_shared].iter().map(|&i| change[i].score).collect();
+    println!(
+        "Mean Neighborhood {} (top {}, shared genes): {}",
+        metric,
+        k,
+        math::mean(&scores)
+    );
+    println!("Most Changed Genes:");
+    for record in records.iter().take((*top).min(n_shared)) {
+        println!(
+            "{}\t{}\t{}\t{}",
+            record.gene, record.degree_a, record.degree_b, record.score
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use csv::WriterBuilder;
+
+use crate::io;
+use crate::partition::{self, Contingency};
+
+#[derive(Debug, Serialize)]
+struct BestMatchRecord<'a> {
+    table: &'a str,
+    cluster: &'a str,
+    size: usize,
+    best_match: &'a str,
+    best_match_size: usize,
+    overlap: usize,
+    jaccard: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct SummaryRecord {
+    shared_genes: usize,
+    only_a: usize,
+    only_b: usize,
+    clusters_a: usize,
+    clusters_b: usize,
+    adjusted_rand_index: f64,
+    normalized_mutual_information: f64,
+    variation_of_information: f64,
+}
+
+/// Labels of the shared genes as indices into the list of cluster names.
+fn encode(labels: &[&str]) -> (Vec<String>, Vec<usize>) {
+    let mut names: Vec<String> = vec![];
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    let codes = labels
+        .iter()
+        .map(|&l| {
+            *index.entry(l).or_insert_with(|| {
+                names.push(l.to_string());
+                names.len() - 1
+            })
+        })
+        .collect();
+    (names, codes)
+}
+
+pub fn parse_args(clusters_a: &Path, clusters_b: &Path, output: Option<&PathBuf>) -> Result<()> {
+    let a = io::read_cluster_table(clusters_a)?;
+    let b: HashMap<String, String> = io::read_cluster_table(clusters_b)?.into_iter().collect();
+
+    let shared: Vec<(&str, &str)> = a
+        .iter()
+        .filter_map(|(g, ca)| b.get(g).map(|cb| (ca.as_str(), cb.as_str())))
+        .collect();
+    if shared.is_empty() {
+        return Err(anyhow!(
+            "no genes are shared between the two cluster tables"
+        ));
+    }
+    // genes are unique in each table, so the genes of a not shared are only in a
+    let only_a = a.len() - shared.len();
+    let only_b = b.len() - shared.len();
+    info!(
+        "{} shared genes, {} only in {:?}, {} only in {:?}",
+        shared.len(),
+        only_a,
+        clusters_a,
+        only_b,
+        clusters_b
+    );
+
+    let (names_a, codes_a) = encode(&shared.iter().map(|x| x.0).collect::<Vec<&str>>());
+    let (names_b, codes_b) = encode(&shared.iter().map(|x| x.1).collect::<Vec<&str>>());
+    let c = Contingency::new(&codes_a, &codes_b);
+
+    let default_path = PathBuf::from("compare_clusters");
+    let prefix = output.unwrap_or(&default_path);
+
+    // full Jaccard matrix, clusters of a in rows
+    let mut wtr = WriterBuilder::new()
+        .delimiter(b'\t')
+        .from_path(prefix.with_extension("jaccard.tsv"))?;
+    let mut header = vec!["cluster".to_string()];
+    header.extend(names_b.iter().cloned());
+    wtr.write_record(&header)?;
+    for (i, name) in names_a.iter().enumerate() {
+        let mut record = vec![name.clone()];
+        record.extend((0..names_b.len()).map(|j| c.jaccard(i, j).to_string()));
+        wtr.write_record(&record)?;
+    }
+    wtr.flush()?;
+
+    let mut wtr = WriterBuilder::new()
+        .delimiter(b'\t')
+        .from_path(prefix.with_extension("best_match.tsv"))?;
+    for (i, name) in names_a.iter().enumerate() {
+        let j = (0..names_b.len())
+            .max_by(|&x, &y| c.jaccard(i, x).partial_cmp(&c.jaccard(i, y)).unwrap())
+            .unwrap();
+        wtr.serialize(BestMatchRecord {
+            table: "a",
+            cluster: name,
+            size: c.a_sizes[i],
+            best_match: &names_b[j],
+            best_match_size: c.b_sizes[j],
+            overlap: c.counts.get(&(i, j)).cloned().unwrap_or(0),
+            jaccard: c.jaccard(i, j),
+        })?;
+    }
+    for (j, name) in names_b.iter().enumerate() {
+        let i = (0..names_a.len())
+            .max_by(|&x, &y| c.jaccard(x, j).partial_cmp(&c.jaccard(y, j)).unwrap())
+            .unwrap();
+        wtr.serialize(BestMatchRecord {
+            table: "b",
+            cluster: name,
+            size: c.b_sizes[j],
+            best_match: &names_a[i],
+            best_match_size: c.a_sizes[i],
+            overlap: c.counts.get(&(i, j)).cloned().unwrap_or(0),
+            jaccard: c.jaccard(i, j),
+        })?;
+    }
+    wtr.flush()?;
+
+    let summary = SummaryRecord {
+        shared_genes: shared.len(),
+        only_a,
+        only_b,
+        clusters_a: names_a.len(),
+        clusters_b: names_b.len(),
+        adjusted_rand_index: partition::adjusted_rand_index(&c),
+        normalized_mutual_information: partition::normalized_mutual_information(&c),
+        variation_of_information: partition::variation_of_information(&c),
+    };
+    println!(
+        "ARI: {}\nNMI: {}\nVI: {}",
+        summary.adjusted_rand_index,
+        summary.normalized_mutual_information,
+        summary.variation_of_information
+    );
+
+    let mut wtr = WriterBuilder::new()
+        .delimiter(b'\t')
+        .from_path(prefix.with_extension("summary.tsv"))?;
+    wtr.serialize(summary)?;
+    wtr.flush()?;
+
+    Ok(())
+}
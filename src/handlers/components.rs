@@ -0,0 +1,86 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use csv::{Reader, WriterBuilder};
+
+use crate::components;
+use crate::graph::Csr;
+use crate::id_map;
+use crate::io;
+
+#[derive(Debug, Serialize)]
+struct ComponentRecord<'a> {
+    gene: &'a str,
+    component: usize,
+    component_size: usize,
+    core: usize,
+}
+
+pub fn parse_args(
+    input: &Path,
+    output: Option<&PathBuf>,
+    largest_component: &bool,
+    kcore: Option<&usize>,
+    filter_output: Option<&PathBuf>,
+) -> Result<()> {
+    info!("--- start read {:?} ---", input);
+    let (nodes, pairs) = io::read_edge_pairs(input)?;
+
+    info!("label connected components...");
+    let labels = components::connected_components_from_pairs(nodes.len(), &pairs);
+    let sizes = components::component_sizes(&labels);
+    info!("{} connected components", sizes.len());
+
+    info!("calculate k-core decomposition...");
+    let csr = Csr::from_pairs(nodes.len(), &pairs);
+    drop(pairs);
+    let cores = components::core_numbers(&csr);
+    drop(csr);
+
+    let default_path = PathBuf::from("components.tsv");
+    let out_path = output.unwrap_or(&default_path);
+    let mut wtr = WriterBuilder::new().delimiter(b'\t').from_path(out_path)?;
+    for (i, gene) in nodes.iter().enumerate() {
+        wtr.serialize(ComponentRecord {
+            gene,
+            component: labels[i],
+            component_size: sizes[labels[i]],
+            core: cores[i],
+        })?;
+    }
+    wtr.flush()?;
+
+    if let Some(filter_output) = filter_output {
+        // the largest component, the k-core, or the k-core of the largest component
+        let largest = (0..sizes.len()).max_by_key(|&c| sizes[c]);
+        let keep: HashSet<&str> = nodes
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| !*largest_component || Some(labels[i]) == largest)
+            .filter(|&(i, _)| kcore.is_none_or(|k| cores[i] >= *k))
+            .map(|(_, gene)| gene.as_str())
+            .collect();
+        info!("write edges between {} genes", keep.len());
+
+        let mut rdr = Reader::from_reader(io::open_with_gz(input)?);
+        let mut wtr = csv::Writer::from_writer(io::create_with_gz(filter_output)?);
+        wtr.write_byte_record(rdr.byte_headers()?)?;
+
+        let mut raw_record = csv::ByteRecord::new();
+        while rdr.read_byte_record(&mut raw_record)? {
+            let gene_1 = std::str::from_utf8(&raw_record[0])?;
+            let gene_2 = std::str::from_utf8(&raw_record[1])?;
+            if keep.contains(id_map::map_id(gene_1).as_ref())
+                && keep.contains(id_map::map_id(gene_2).as_ref())
+            {
+                wtr.write_byte_record(&raw_record)?;
+            }
+        }
+        wtr.flush()?;
+    }
+
+    info!("Finish!");
+
+    Ok(())
+}
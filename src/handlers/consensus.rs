@@ -0,0 +1,162 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use csv::Writer;
+use ndarray_stats::*;
+
+use crate::consensus::{self, Dataset};
+use crate::graph::RankedEdge;
+use crate::io;
+use crate::rank;
+use crate::sweep;
+use crate::Aggregation;
+use crate::Rank;
+
+#[derive(Debug, Serialize)]
+struct ConsensusRecord<'a> {
+    gene_1: &'a str,
+    gene_2: &'a str,
+    corr: f64,
+    rank: usize,
+    support: usize,
+    datasets: usize,
+    mean_rank: f64,
+    median_rank: f64,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn read_matrix(
+    path: &Path,
+    log2: bool,
+    pseudo_count: f64,
+    method: &Rank,
+    rank_cutoff: f64,
+    genes: &mut io::GeneIndex,
+) -> Result<(Vec<usize>, Vec<RankedEdge>, usize)> {
+    let mut index = vec![];
+    let mut arr = io::read_exp_csv(path, &mut index)?;
+    if log2 {
+        arr.par_mapv_inplace(|x| (x + pseudo_count).log2());
+    }
+    let corr = arr.pearson_correlation()?;
+    let rank_arr = rank::construct_rank_matrix_multithreading(&corr, index.len())?;
+    let to_shared: Vec<usize> = index.into_iter().map(|name| genes.intern(name)).collect();
+    let edges = sweep::ranked_edges(&corr, &rank_arr, method, rank_cutoff)
+        .into_iter()
+        .map(|(i, j, corr, rank)| (to_shared[i], to_shared[j], corr, rank))
+        .collect();
+    Ok((to_shared, edges, arr.ncols()))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn parse_args(
+    networks: &[PathBuf],
+    samples: &[usize],
+    matrices: &[PathBuf],
+    rank_column: Option<&String>,
+    log2: &bool,
+    pseudo_count: &f64,
+    method: &Rank,
+    rank_cutoff: &f64,
+    aggregation: &Aggregation,
+    min_support: &usize,
+    output: Option<&PathBuf>,
+) -> Result<()> {
+    let n_datasets = networks.len() + matrices.len();
+    if n_datasets < 2 {
+        bail!("consensus needs at least two networks or expression matrices");
+    }
+    if *min_support > n_datasets {
+        bail!(
+            "--min_support {} is larger than the {} datasets",
+            min_support,
+            n_datasets
+        );
+    }
+
+    if !samples.is_empty() && samples.len() != networks.len() {
+        bail!(
+            "--samples needs one sample count per network, got {} for {}",
+            samples.len(),
+            networks.len()
+        );
+    }
+
+    let mut genes = io::GeneIndex::default();
+    let mut members: Vec<Vec<usize>> = vec![];
+    let mut partial: Vec<(Vec<RankedEdge>, Option<usize>)> = vec![];
+    for (n, path) in networks.iter().enumerate() {
+        info!("read {:?}", path);
+        let (to_shared, edges) =
+            io::read_graph_shared(path, rank_column.map(|x| x.as_str()), &mut genes)?;
+        members.push(to_shared);
+        partial.push((edges, samples.get(n).cloned()));
+    }
+    for path in matrices.iter() {
+        info!(
+            "read {:?} and rank pairs within {} {}",
+            path, method, rank_cutoff
+        );
+        let (to_shared, edges, samples) =
+            read_matrix(path, *log2, *pseudo_count, method, *rank_cutoff, &mut genes)?;
+        members.push(to_shared);
+        partial.push((edges, Some(samples)));
+    }
+
+    // the shared index is complete only after every input is read
+    let genes = genes.names;
+    let datasets: Vec<Dataset> = members
+        .iter()
+        .zip(partial)
+        .map(|(to_shared, (edges, samples))| {
+            let mut measured = vec![false; genes.len()];
+            to_shared.iter().for_each(|&i| measured[i] = true);
+            Dataset {
+                edges,
+                genes: measured,
+                samples,
+            }
+        })
+        .collect();
+    for (d, dataset) in datasets.iter().enumerate() {
+        info!(
+            "dataset {}: {} genes, {} edges",
+            d + 1,
+            members[d].len(),
+            dataset.edges.len()
+        );
+    }
+
+    if datasets.iter().any(|d| d.samples.is_none()) {
+        info!("correlations are combined with equal weights, give --samples to weight networks");
+    }
+    info!("aggregate by {}...", aggregation);
+    let edges = consensus::consensus(&datasets, aggregation, *min_support);
+
+    let default_path = PathBuf::from("consensus_network.csv");
+    let out_path = output.unwrap_or(&default_path);
+    let mut wtr = Writer::from_writer(io::create_with_gz(out_path)?);
+    for (r, e) in edges.iter().enumerate() {
+        wtr.serialize(ConsensusRecord {
+            gene_1: &genes[e.gene_1],
+            gene_2: &genes[e.gene_2],
+            corr: e.corr,
+            rank: r + 1,
+            support: e.support,
+            datasets: e.datasets,
+            mean_rank: e.mean_rank,
+            median_rank: e.median_rank,
+        })?;
+    }
+    wtr.flush()?;
+
+    println!("Datasets: {}", n_datasets);
+    println!("Genes: {}", genes.len());
+    println!("Edges: {}", edges.len());
+    println!("Support:");
+    for s in (1..=n_datasets).rev() {
+        println!("{}\t{}", s, edges.iter().filter(|e| e.support == s).count());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use ndarray::{Array2, Axis};
+use ndarray_stats::*;
+
+use crate::diffcoex;
+use crate::io;
+use crate::math;
+use crate::rank;
+use crate::Rank;
+
+#[derive(Debug, Serialize)]
+struct EdgeRecord<'a> {
+    gene_1: &'a str,
+    gene_2: &'a str,
+    corr_a: f64,
+    corr_b: f64,
+    delta_corr: f64,
+    rank_a: f64,
+    rank_b: f64,
+    rank_change: f64,
+    z: f64,
+    pvalue: f64,
+    padj: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct GeneRecord<'a> {
+    gene: &'a str,
+    tested: usize,
+    significant: usize,
+    strengthened: usize,
+    weakened: usize,
+    fraction: f64,
+    mean_abs_delta_corr: f64,
+}
+
+/// Genes and the expression of conditions a and b, on the same genes.
+type Conditions = (Vec<String>, Array2<f64>, Array2<f64>);
+
+fn read_two_matrices(input_a: &Path, input_b: &Path) -> Result<Conditions> {
+    let (mut index_a, mut index_b) = (vec![], vec![]);
+    let arr_a = io::read_exp_csv(input_a, &mut index_a)?;
+    let arr_b = io::read_exp_csv(input_b, &mut index_b)?;
+    let pos_b: HashMap<&str, usize> = index_b
+        .iter()
+        .enumerate()
+        .map(|(i, g)| (g.as_str(), i))
+        .collect();
+    let (rows_a, rows_b): (Vec<usize>, Vec<usize>) = index_a
+        .iter()
+        .enumerate()
+        .filter_map(|(i, g)| pos_b.get(g.as_str()).map(|&j| (i, j)))
+        .unzip();
+    info!(
+        "{} genes in both matrices, {} and {} in one only",
+        rows_a.len(),
+        index_a.len() - rows_a.len(),
+        index_b.len() - rows_b.len()
+    );
+    let genes = rows_a.iter().map(|&i| index_a[i].clone()).collect();
+    Ok((
+        genes,
+        arr_a.select(Axis(0), &rows_a),
+        arr_b.select(Axis(0), &rows_b),
+    ))
+}
+
+fn split_matrix(
+    input: &Path,
+    metadata: &Path,
+    column: &str,
+    groups: &[String],
+) -> Result<Conditions> {
+    if groups.len() != 2 {
+        bail!("--groups needs two values of {}", column);
+    }
+    let mut index = vec![];
+    let arr = io::read_exp_csv(input, &mut index)?;
+    let samples = io::read_exp_samples(input)?;
+    let condition: HashMap<String, String> = io::read_sample_column(metadata, column)?
+        .into_iter()
+        .collect();
+    let columns = |group: &str| -> Vec<usize> {
+        samples
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| condition.get(*s).map(|c| c.as_str()) == Some(group))
+            .map(|(i, _)| i)
+            .collect()
+    };
+    let (cols_a, cols_b) = (columns(&groups[0]), columns(&groups[1]));
+    info!(
+        "{}: {} samples of {}, {} of {}",
+        column,
+        cols_a.len(),
+        groups[0],
+        cols_b.len(),
+        groups[1]
+    );
+    Ok((
+        index,
+        arr.select(Axis(1), &cols_a),
+        arr.select(Axis(1), &cols_b),
+    ))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn parse_args(
+    input_a: Option<&PathBuf>,
+    input_b: Option<&PathBuf>,
+    input: Option<&PathBuf>,
+    metadata: Option<&PathBuf>,
+    column: Option<&String>,
+    groups: &[String],
+    log2: &bool,
+    pseudo_count: &f64,
+    method: &Rank,
+    rank_cutoff: &f64,
+    padj_cutoff: &f64,
+    output: Option<&PathBuf>,
+) -> Result<()> {
+    let (genes, mut arr_a, mut arr_b) = match (input_a, input_b, input) {
+        (Some(input_a), Some(input_b), None) => read_two_matrices(input_a, input_b)?,
+        (None, None, Some(input)) => {
+            let metadata = metadata.ok_or_else(|| anyhow!("-i needs --metadata"))?;
+            let column = column.ok_or_else(|| anyhow!("-i needs --column"))?;
+            split_matrix(input, metadata, column, groups)?
+        }
+        _ => bail!("give either two matrices (-a, -b) or one matrix (-i) with --metadata"),
+    };
+    let (n_a, n_b) = (arr_a.ncols(), arr_b.ncols());
+    if n_a < 4 || n_b < 4 {
+        bail!(
+            "each condition needs at least 4 samples, got {} and {}",
+            n_a,
+            n_b
+        );
+    }
+    if *log2 {
+        arr_a.par_mapv_inplace(|x| (x + pseudo_count).log2());
+        arr_b.par_mapv_inplace(|x| (x + pseudo_count).log2());
+    }
+
+    // correlation is undefined for genes constant in a condition
+    let rows: Vec<usize> = (0..genes.len())
+        .filter(|&i| {
+            [&arr_a, &arr_b]
+                .iter()
+                .all(|arr| math::std(&arr.row(i).to_vec(), 1.) > 0.)
+        })
+        .collect();
+    if rows.len() < genes.len() {
+        warn!(
+            "skip {} genes constant in a condition",
+            genes.len() - rows.len()
+        );
+    }
+    let genes: Vec<String> = rows.iter().map(|&i| genes[i].clone()).collect();
+    let arr_a = arr_a.select(Axis(0), &rows);
+    let arr_b = arr_b.select(Axis(0), &rows);
+
+    info!("calculate correlation and rank matrices...");
+    let corr_a = arr_a.pearson_correlation()?;
+    let corr_b = arr_b.pearson_correlation()?;
+    let rank_a = rank::construct_rank_matrix_multithreading(&corr_a, genes.len())?;
+    let rank_b = rank::construct_rank_matrix_multithreading(&corr_b, genes.len())?;
+
+    info!("test gene pairs within {} {}...", method, rank_cutoff);
+    let edges = diffcoex::diff_edges(
+        &corr_a,
+        &rank_a,
+        n_a,
+        &corr_b,
+        &rank_b,
+        n_b,
+        method,
+        *rank_cutoff,
+    );
+    let rewiring = diffcoex::rewiring(genes.len(), &edges, *padj_cutoff);
+
+    let default_path = PathBuf::from("diffcoex");
+    let prefix = output.unwrap_or(&default_path);
+    let edge_records: Vec<EdgeRecord> = edges
+        .iter()
+        .map(|e| EdgeRecord {
+            gene_1: &genes[e.gene_1],
+            gene_2: &genes[e.gene_2],
+            corr_a: e.corr_a,
+            corr_b: e.corr_b,
+            delta_corr: e.delta_corr(),
+            rank_a: e.rank_a,
+            rank_b: e.rank_b,
+            rank_change: e.rank_change(),
+            z: e.z,
+            pvalue: e.pvalue,
+            padj: e.padj,
+        })
+        .collect();
+    io::write_tsv(prefix.with_extension("edges.tsv"), &edge_records)?;
+
+    let mut order: Vec<usize> = (0..genes.len())
+        .filter(|&i| rewiring[i].tested > 0)
+        .collect();
+    order.sort_by(|&i, &j| {
+        rewiring[j]
+            .significant
+            .cmp(&rewiring[i].significant)
+            .then(rewiring[j].fraction().total_cmp(&rewiring[i].fraction()))
+            .then(genes[i].cmp(&genes[j]))
+    });
+    let gene_records: Vec<GeneRecord> = order
+        .iter()
+        .map(|&i| GeneRecord {
+            gene: &genes[i],
+            tested: rewiring[i].tested,
+            significant: rewiring[i].significant,
+            strengthened: rewiring[i].strengthened,
+            weakened: rewiring[i].weakened,
+            fraction: rewiring[i].fraction(),
+            mean_abs_delta_corr: rewiring[i].mean_abs_delta_corr,
+        })
+        .collect();
+    io::write_tsv(prefix.with_extension("genes.tsv"), &gene_records)?;
+
+    let significant = edges.iter().filter(|e| e.padj <= *padj_cutoff).count();
+    println!("Genes: {}", genes.len());
+    println!("Samples: {} / {}", n_a, n_b);
+    println!("Tested Pairs: {}", edges.len());
+    println!(
+        "Significant Pairs (padj <= {}): {}",
+        padj_cutoff, significant
+    );
+    println!(
+        "Rewired Genes: {}",
+        gene_records.iter().filter(|r| r.significant > 0).count()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,145 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use csv::WriterBuilder;
+use ndarray::Axis;
+
+use crate::eigengene;
+use crate::io;
+use crate::math;
+
+#[derive(Debug, Serialize)]
+struct ModuleRecord<'a> {
+    cluster: &'a str,
+    genes: usize,
+    variance_explained: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct TraitRecord<'a> {
+    cluster: &'a str,
+    #[serde(rename = "trait")]
+    trait_name: &'a str,
+    samples: usize,
+    corr: f64,
+    pvalue: f64,
+}
+
+pub fn parse_args(
+    clusters: &Path,
+    input: &Path,
+    traits: Option<&PathBuf>,
+    output: Option<&PathBuf>,
+    log2: &bool,
+    pseudo_count: &f64,
+) -> Result<()> {
+    info!("--- start read {}  ---", input.to_str().unwrap());
+    let mut index: Vec<String> = vec![];
+    let mut arr = io::read_exp_csv(input, &mut index)?;
+    if *log2 {
+        arr.par_mapv_inplace(|x| (x + pseudo_count).log2());
+    }
+    let samples = io::read_exp_samples(input)?;
+    let gene_index: HashMap<&str, usize> = index
+        .iter()
+        .enumerate()
+        .map(|(i, g)| (g.as_str(), i))
+        .collect();
+
+    // cluster -> rows of the expression matrix
+    let mut modules: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    let mut unmatched = 0;
+    for (gene, cluster) in io::read_cluster_table(clusters)? {
+        match gene_index.get(gene.as_str()) {
+            Some(&i) => modules.entry(cluster).or_default().push(i),
+            None => unmatched += 1,
+        }
+    }
+    if unmatched > 0 {
+        warn!(
+            "{} genes in the cluster table are not in the expression matrix",
+            unmatched
+        );
+    }
+    info!("{} modules", modules.len());
+
+    let default_path = PathBuf::from("eigengene");
+    let prefix = output.unwrap_or(&default_path);
+
+    let mut eigengenes: Vec<(&str, Vec<f64>)> = vec![];
+    let mut wtr = WriterBuilder::new()
+        .delimiter(b'\t')
+        .from_path(prefix.with_extension("variance.tsv"))?;
+    for (cluster, rows) in modules.iter() {
+        let (me, explained) = eigengene::eigengene(&arr.select(Axis(0), rows));
+        wtr.serialize(ModuleRecord {
+            cluster,
+            genes: rows.len(),
+            variance_explained: explained,
+        })?;
+        eigengenes.push((cluster, me.to_vec()));
+    }
+    wtr.flush()?;
+
+    // samples x eigengenes
+    let mut wtr = WriterBuilder::new()
+        .delimiter(b'\t')
+        .from_path(prefix.with_extension("eigengenes.tsv"))?;
+    let mut header = vec!["sample".to_string()];
+    header.extend(eigengenes.iter().map(|(c, _)| format!("ME{}", c)));
+    wtr.write_record(&header)?;
+    for (s, sample) in samples.iter().enumerate() {
+        let mut record = vec![sample.clone()];
+        record.extend(eigengenes.iter().map(|(_, me)| me[s].to_string()));
+        wtr.write_record(&record)?;
+    }
+    wtr.flush()?;
+
+    if let Some(traits) = traits {
+        info!("correlate eigengenes with {:?}", traits);
+        let (trait_samples, trait_names, trait_arr) = io::read_numeric_csv(traits)?;
+        let trait_row: HashMap<&str, usize> = trait_samples
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.as_str(), i))
+            .collect();
+        // position in the trait table of each expression sample
+        let matched: Vec<(usize, usize)> = samples
+            .iter()
+            .enumerate()
+            .filter_map(|(s, name)| trait_row.get(name.as_str()).map(|&t| (s, t)))
+            .collect();
+        if matched.len() < 3 {
+            return Err(anyhow!(
+                "only {} samples are shared between the expression matrix and the trait table",
+                matched.len()
+            ));
+        }
+        info!("{} samples are matched", matched.len());
+
+        let mut wtr = WriterBuilder::new()
+            .delimiter(b'\t')
+            .from_path(prefix.with_extension("traits.tsv"))?;
+        for (cluster, me) in eigengenes.iter() {
+            for (j, trait_name) in trait_names.iter().enumerate() {
+                let x: Vec<f64> = matched.iter().map(|&(s, _)| me[s]).collect();
+                let y: Vec<f64> = matched.iter().map(|&(_, t)| trait_arr[[t, j]]).collect();
+                let n = y.iter().filter(|v| !v.is_nan()).count();
+                let corr = math::pearson(&x, &y);
+                wtr.serialize(TraitRecord {
+                    cluster,
+                    trait_name,
+                    samples: n,
+                    corr,
+                    pvalue: math::pearson_pvalue(corr, n),
+                })?;
+            }
+        }
+        wtr.flush()?;
+    }
+
+    info!("Finish!");
+
+    Ok(())
+}
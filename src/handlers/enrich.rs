@@ -0,0 +1,150 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use csv::WriterBuilder;
+
+use crate::enrich::{self, Ontology};
+use crate::io;
+
+#[derive(Debug, Serialize)]
+struct EnrichmentRecord<'a> {
+    set: &'a str,
+    term: &'a str,
+    name: &'a str,
+    set_size: usize,
+    term_size: usize,
+    background_size: usize,
+    overlap: usize,
+    expected: f64,
+    fold_enrichment: f64,
+    pvalue: f64,
+    padj: f64,
+    genes: String,
+}
+
+fn set_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn parse_args(
+    in_graph: &Path,
+    annotation_path: &Path,
+    clusters: Option<&PathBuf>,
+    gene_lists: &[PathBuf],
+    neighborhoods: &[PathBuf],
+    obo: Option<&PathBuf>,
+    output: Option<&PathBuf>,
+    min_term_size: &usize,
+    max_term_size: Option<&usize>,
+    padj_cutoff: Option<&f64>,
+) -> Result<()> {
+    let (network_genes, _) = io::read_edge_pairs(in_graph)?;
+    let network_genes: HashSet<String> = network_genes.into_iter().collect();
+
+    // gene sets from every source
+    let mut sets: Vec<(String, Vec<String>)> = vec![];
+    if let Some(clusters) = clusters {
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for (gene, cluster) in io::read_cluster_table(clusters)? {
+            let i = *index.entry(cluster.clone()).or_insert_with(|| {
+                sets.push((cluster, vec![]));
+                sets.len() - 1
+            });
+            sets[i].1.push(gene);
+        }
+    }
+    for path in gene_lists.iter() {
+        sets.push((
+            set_name(path),
+            io::read_gene_list(path)?.into_iter().collect(),
+        ));
+    }
+    for path in neighborhoods.iter() {
+        sets.push((set_name(path), io::read_edge_pairs(path)?.0));
+    }
+    if sets.is_empty() {
+        bail!("no gene set is given, use --clusters, --gene_list or --neighborhood");
+    }
+
+    let mut missing = 0;
+    for (_, genes) in sets.iter_mut() {
+        let n = genes.len();
+        genes.retain(|g| network_genes.contains(g));
+        missing += n - genes.len();
+    }
+    if missing > 0 {
+        warn!("{} genes of the gene sets are not in the network", missing);
+    }
+
+    info!("--- start read {:?} ---", annotation_path);
+    let (mut annotation, descriptions) = io::read_annotation(annotation_path)?;
+    let ontology = match obo {
+        Some(obo) => {
+            let ontology = Ontology::new(&io::read_obo(obo)?);
+            let unknown = ontology.propagate(&mut annotation);
+            if unknown > 0 {
+                warn!("{} annotated terms are not in {:?}", unknown, obo);
+            }
+            Some(ontology)
+        }
+        None => None,
+    };
+
+    // background: annotated genes present in the network
+    annotation.retain(|g, _| network_genes.contains(g));
+    info!(
+        "background: {} annotated genes of {} in the network",
+        annotation.len(),
+        network_genes.len()
+    );
+
+    let res = enrich::enrichment(
+        &sets,
+        &annotation,
+        *min_term_size,
+        *max_term_size.unwrap_or(&usize::MAX),
+    );
+
+    let default_path = PathBuf::from("enrichment.tsv");
+    let out_path = output.unwrap_or(&default_path);
+    let mut wtr = WriterBuilder::new().delimiter(b'\t').from_path(out_path)?;
+    let mut n_significant = 0;
+    for e in res.iter() {
+        if padj_cutoff.is_some_and(|c| e.padj > *c) {
+            continue;
+        }
+        n_significant += 1;
+        let name = ontology
+            .as_ref()
+            .and_then(|o| o.name(&e.term))
+            .or_else(|| descriptions.get(&e.term))
+            .map_or("", |x| x.as_str());
+        wtr.serialize(EnrichmentRecord {
+            set: &e.set,
+            term: &e.term,
+            name,
+            set_size: e.set_size,
+            term_size: e.term_size,
+            background_size: e.background_size,
+            overlap: e.overlap.len(),
+            expected: e.expected(),
+            fold_enrichment: e.overlap.len() as f64 / e.expected(),
+            pvalue: e.pvalue,
+            padj: e.padj,
+            genes: e.overlap.join(","),
+        })?;
+    }
+    wtr.flush()?;
+    info!(
+        "write {} of {} tests to {:?}",
+        n_significant,
+        res.len(),
+        out_path
+    );
+
+    Ok(())
+}
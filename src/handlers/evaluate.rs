@@ -0,0 +1,184 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use csv::WriterBuilder;
+
+use crate::evaluate::{self, GoldStandard};
+use crate::io;
+
+#[derive(Debug, Serialize)]
+struct CurveRecord<'a> {
+    network: &'a str,
+    rank: f64,
+    edges: usize,
+    tp: usize,
+    fp: usize,
+    precision: f64,
+    recall: f64,
+    fpr: f64,
+}
+
+/// Evaluate the ranks in `rank_column` of one network.
+#[allow(clippy::too_many_arguments)]
+fn evaluate_network(
+    path: &Path,
+    rank_column: Option<&str>,
+    label: &str,
+    gold: &GoldStandard,
+    precisions: &[f64],
+    points: usize,
+    curve_wtr: &mut csv::Writer<std::fs::File>,
+) -> Result<Vec<String>> {
+    info!("--- start evaluate {} ---", label);
+    let g = io::read_graph(path, rank_column)?;
+    let genes: Vec<Option<usize>> = g.node_names().iter().map(|x| gold.index(x)).collect();
+
+    let mut edges: Vec<(f64, bool)> = g
+        .edges()
+        .iter()
+        .filter_map(|e| {
+            let (i, j) = (genes[e.query()]?, genes[e.target()]?);
+            Some((e.rank(), gold.is_positive(i, j)))
+        })
+        .collect();
+    let n_edges = g.edges().len();
+    let n_evaluated = edges.len();
+
+    let covered: Vec<usize> = genes.iter().flatten().cloned().collect();
+    let positives = gold.n_positives(&covered);
+    let negatives = covered.len() * covered.len().saturating_sub(1) / 2 - positives;
+    info!(
+        "{} of {} genes in the gold standard, {} positive and {} negative pairs",
+        covered.len(),
+        genes.len(),
+        positives,
+        negatives
+    );
+    if positives == 0 || negatives == 0 {
+        bail!("{}: no positive or negative gene pair to evaluate", label);
+    }
+
+    let curve = evaluate::curve(&mut edges);
+
+    // thin the written curve to about `points` points, always keeping the last one
+    let step = std::cmp::max(n_evaluated / std::cmp::max(points, 1), 1);
+    let mut next = 0;
+    for (i, c) in curve.iter().enumerate() {
+        if c.edges < next && i + 1 != curve.len() {
+            continue;
+        }
+        next = c.edges + step;
+        curve_wtr.serialize(CurveRecord {
+            network: label,
+            rank: c.rank,
+            edges: c.edges,
+            tp: c.tp,
+            fp: c.fp,
+            precision: c.precision(),
+            recall: c.tp as f64 / positives as f64,
+            fpr: c.fp as f64 / negatives as f64,
+        })?;
+    }
+
+    let auroc = evaluate::auroc(&curve, positives, negatives);
+    let auprc = evaluate::auprc(&curve, positives);
+    info!("{}: AUROC {}, AUPRC {}", label, auroc, auprc);
+
+    let tp = curve.last().map_or(0, |c| c.tp);
+    let mut record = vec![
+        label.to_string(),
+        n_edges.to_string(),
+        n_evaluated.to_string(),
+        positives.to_string(),
+        tp.to_string(),
+        auroc.to_string(),
+        auprc.to_string(),
+    ];
+    for p in precisions.iter() {
+        let point = evaluate::at_precision(&curve, *p);
+        record.push(point.map_or(0, |c| c.edges).to_string());
+        record.push(point.map_or(String::new(), |c| c.rank.to_string()));
+    }
+    Ok(record)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn parse_args(
+    inputs: &[PathBuf],
+    rank_columns: &[String],
+    pairs: Option<&PathBuf>,
+    annotation: Option<&PathBuf>,
+    max_term_size: &usize,
+    output: Option<&PathBuf>,
+    precisions: &[f64],
+    points: &usize,
+) -> Result<()> {
+    let gold = match (pairs, annotation) {
+        (Some(pairs), None) => GoldStandard::from_pairs(&io::read_gene_pairs(pairs)?),
+        (None, Some(annotation)) => {
+            let (annotation, _) = io::read_annotation(annotation)?;
+            GoldStandard::from_annotation(&annotation, *max_term_size)
+        }
+        _ => bail!("give exactly one gold standard, --pairs or --annotation"),
+    };
+
+    let default_path = PathBuf::from("evaluate");
+    let prefix = output.unwrap_or(&default_path);
+    let mut curve_wtr = WriterBuilder::new()
+        .delimiter(b'\t')
+        .from_path(prefix.with_extension("curve.tsv"))?;
+    let mut summary_wtr = WriterBuilder::new()
+        .delimiter(b'\t')
+        .from_path(prefix.with_extension("summary.tsv"))?;
+
+    let mut header: Vec<String> = vec![
+        "network",
+        "edges",
+        "evaluated_edges",
+        "positives",
+        "tp",
+        "auroc",
+        "auprc",
+    ]
+    .into_iter()
+    .map(|x| x.to_string())
+    .collect();
+    for p in precisions.iter() {
+        header.push(format!("edges_at_precision_{}", p));
+        header.push(format!("rank_at_precision_{}", p));
+    }
+    summary_wtr.write_record(&header)?;
+
+    let columns: Vec<Option<&str>> = if rank_columns.is_empty() {
+        vec![None]
+    } else {
+        rank_columns.iter().map(|x| Some(x.as_str())).collect()
+    };
+    for input in inputs.iter() {
+        let stem = input
+            .file_name()
+            .map_or(String::new(), |x| x.to_string_lossy().to_string());
+        for column in columns.iter() {
+            let label = match column {
+                Some(c) => format!("{}:{}", stem, c),
+                None => stem.clone(),
+            };
+            let record = evaluate_network(
+                input,
+                *column,
+                &label,
+                &gold,
+                precisions,
+                *points,
+                &mut curve_wtr,
+            )?;
+            summary_wtr.write_record(&record)?;
+        }
+    }
+    curve_wtr.flush()?;
+    summary_wtr.flush()?;
+
+    info!("Finish!");
+
+    Ok(())
+}
@@ -4,6 +4,7 @@ use std::path::{Path, PathBuf};
 use anyhow::Result;
 use csv::{Reader, Writer};
 
+use crate::id_map;
 use crate::io;
 
 pub fn parse_args(
@@ -34,7 +35,9 @@ pub fn parse_args(
         let (gene_1, gene_2) = r.genes_unchecked();
 
         if let Some(gene_set) = gene_set.as_ref() {
-            if !(gene_set.contains(&gene_1) || gene_set.contains(&gene_2)) {
+            if !(gene_set.contains(id_map::map_id(&gene_1).as_ref())
+                || gene_set.contains(id_map::map_id(&gene_2).as_ref()))
+            {
                 continue;
             }
         }
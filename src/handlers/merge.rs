@@ -1,101 +1,98 @@
-use std::{ffi::OsStr, fs::File, path::Path};
-
-use anyhow::Result;
-use flate2::{Compression, GzBuilder};
-use polars::prelude::*;
-
-use crate::Rank;
-
-fn read_graph_by_poloars(path: &Path, rank: &Rank) -> Result<DataFrame> {
-    let schema = Schema::new(vec![
-        Field::new("gene_1", DataType::Utf8),
-        Field::new("gene_2", DataType::Utf8),
-        Field::new("corr", DataType::Float64),
-        Field::new("rank", DataType::Float64),
-    ]);
-
-    let mut df = CsvReader::from_path(path)?
-        .has_header(true)
-        .with_schema(&schema)
-        .finish()?;
-
-    let col_name = match rank {
-        Rank::HRR => "hrr_rank",
-        Rank::MR => "mr_rank",
-    };
-
-    df.rename("rank", col_name)?;
-
-    Ok(df)
-}
-
-fn add_relation(df: &mut DataFrame) -> Result<()> {
-    let gene1chunk = df.column("gene_1")?.utf8()?;
-    // Either ownership is required for add op with "-"
-    let gene2chunk = df.column("gene_2")?.utf8()?;
-    let genechunk = gene1chunk + "-" + gene2chunk.to_owned();
-    df.replace_or_add("relation", genechunk.into_series())?;
-    Ok(())
-}
-
-fn filterout_by_rank_and_merge(
-    df: &DataFrame,
-    other: &DataFrame,
-    priority: &Rank,
-    max_rank: f64,
-) -> Result<DataFrame> {
-    let col_name = match priority {
-        Rank::HRR => "hrr_rank",
-        Rank::MR => "mr_rank",
-    };
-
-    let mask = df.column(col_name)?.f64()?.lt_eq(max_rank);
-    let df = df.filter(&mask)?;
-
-    let merged_df = df
-        .inner_join(other, "relation", "relation")?
-        .select(vec!["gene_1", "gene_2", "corr", "hrr_rank", "mr_rank"])?;
-    Ok(merged_df)
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use csv::Writer;
+
+use crate::graph::RankedEdge;
+use crate::io;
+use crate::merge;
+use crate::Join;
+
+/// Split `label=path`; a bare path is labelled by its file name up to the first dot.
+fn parse_input(input: &str) -> Result<(String, PathBuf)> {
+    match input.split_once('=') {
+        Some((label, path)) if !label.is_empty() => Ok((label.to_string(), PathBuf::from(path))),
+        Some(_) => bail!("empty label in {}", input),
+        None => {
+            let path = PathBuf::from(input);
+            let label = path
+                .file_name()
+                .and_then(|x| x.to_str())
+                .and_then(|x| x.split('.').next())
+                .ok_or_else(|| anyhow!("cannot label {}, give it as label=path", input))?
+                .to_string();
+            Ok((label, path))
+        }
+    }
 }
 
 pub fn parse_args(
-    hrr_path: &Path,
-    mr_path: &Path,
-    out_path: &Path,
-    priority: &Rank,
+    inputs: &[String],
+    rank_column: Option<&String>,
+    join: &Join,
     max_rank: &f64,
+    out_path: &Path,
 ) -> Result<()> {
+    let inputs: Vec<(String, PathBuf)> = inputs
+        .iter()
+        .map(|x| parse_input(x))
+        .collect::<Result<_>>()?;
+    let mut labels: HashSet<&str> = HashSet::new();
+    if let Some((label, _)) = inputs
+        .iter()
+        .find(|(label, _)| !labels.insert(label.as_str()))
+    {
+        bail!("label {} is given twice", label);
+    }
     info!(
-        "\n hrr graph path: {:?}\n mr graph path: {:?}\n out_path: {:?}\n priority rank: {}, max rank: {}",
-        hrr_path, mr_path, out_path, priority, max_rank
+        "merge {:?} by {} join",
+        inputs.iter().map(|x| &x.0).collect::<Vec<_>>(),
+        join.to_string().to_lowercase()
     );
 
-    let mut hrr_df = read_graph_by_poloars(hrr_path, &Rank::HRR)?;
-    let mut mr_df = read_graph_by_poloars(mr_path, &Rank::MR)?;
-
-    add_relation(&mut hrr_df)?;
-    add_relation(&mut mr_df)?;
-
-    let merged_df = match priority {
-        Rank::HRR => filterout_by_rank_and_merge(&hrr_df, &mr_df, priority, *max_rank),
-        Rank::MR => filterout_by_rank_and_merge(&mr_df, &hrr_df, priority, *max_rank),
-    }?;
+    let mut genes = io::GeneIndex::default();
+    let mut edges: Vec<Vec<RankedEdge>> = vec![];
+    for (label, path) in inputs.iter() {
+        info!("read {} from {:?}", label, path);
+        let (_, e) = io::read_graph_shared(path, rank_column.map(|x| x.as_str()), &mut genes)?;
+        edges.push(e);
+    }
+    let genes = genes.names;
+    let n_edges = edges[0].len();
+    edges[0].retain(|e| e.3 <= *max_rank);
+    info!(
+        "{}: keep {} of {} edges within rank {}",
+        inputs[0].0,
+        edges[0].len(),
+        n_edges,
+        max_rank
+    );
 
-    let ext = out_path.extension();
+    let merged = merge::merge_edges(&edges, join);
 
-    let mut f = File::create(out_path)?;
-    if ext == Some(OsStr::new("gz")) {
-        let mut gz = GzBuilder::new().write(f, Compression::default());
-        CsvWriter::new(&mut gz)
-            .has_header(true)
-            .with_delimiter(b',')
-            .finish(&merged_df)?;
-    } else {
-        CsvWriter::new(&mut f)
-            .has_header(true)
-            .with_delimiter(b',')
-            .finish(&merged_df)?;
+    let mut wtr = Writer::from_writer(io::create_with_gz(out_path)?);
+    let mut header = vec!["gene_1".to_string(), "gene_2".to_string()];
+    for (label, _) in inputs.iter() {
+        header.push(format!("corr_{}", label));
+        header.push(format!("rank_{}", label));
+    }
+    wtr.write_record(&header)?;
+    for e in merged.iter() {
+        let mut record = vec![genes[e.gene_1].clone(), genes[e.gene_2].clone()];
+        for v in e.values.iter() {
+            match v {
+                Some((corr, rank)) => {
+                    record.push(corr.to_string());
+                    record.push(rank.to_string());
+                }
+                None => record.extend(vec![String::new(); 2]),
+            }
+        }
+        wtr.write_record(&record)?;
     }
+    wtr.flush()?;
+    info!("write {} edges to {:?}", merged.len(), out_path);
 
     Ok(())
 }
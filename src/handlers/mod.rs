@@ -1,6 +1,21 @@
+pub mod centrality;
+pub mod cluster_quality;
 pub mod clustering;
+pub mod codon_bias;
 pub mod codon_usage;
+pub mod compare;
+pub mod compare_clusters;
+pub mod components;
+pub mod consensus;
 pub mod construct;
+pub mod diffcoex;
+pub mod eigengene;
+pub mod enrich;
+pub mod evaluate;
 pub mod extract;
 pub mod merge;
+pub mod predict;
 pub mod query;
+pub mod stats;
+pub mod sweep;
+pub mod wgcna;
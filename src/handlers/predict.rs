@@ -0,0 +1,180 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use csv::WriterBuilder;
+use rayon::prelude::*;
+
+use crate::enrich::Ontology;
+use crate::io;
+use crate::math;
+use crate::predict;
+use crate::rank;
+use crate::Weight;
+
+#[derive(Debug, Serialize)]
+struct PredictionRecord<'a> {
+    term: &'a str,
+    name: &'a str,
+    gene: &'a str,
+    score: f64,
+    rank: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct AurocRecord<'a> {
+    term: &'a str,
+    name: &'a str,
+    size: usize,
+    auroc: f64,
+    degree_auroc: f64,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn parse_args(
+    in_graph: &Path,
+    annotation_path: &Path,
+    obo: Option<&PathBuf>,
+    output: Option<&PathBuf>,
+    rank_column: Option<&String>,
+    weight: &Weight,
+    decay: &f64,
+    min_term_size: &usize,
+    max_term_size: &usize,
+    cv: Option<&usize>,
+    seed: &u64,
+    top: Option<&usize>,
+) -> Result<()> {
+    info!("--- start read {:?} ---", in_graph);
+    let g = io::read_graph(in_graph, rank_column.map(|x| x.as_str()))?;
+    let csr = g.to_weighted_csr(|e| rank::to_weight(weight, e.corr(), e.rank(), *decay));
+    let genes = g.node_names();
+    let gene_index: HashMap<&str, usize> = genes
+        .iter()
+        .enumerate()
+        .map(|(i, x)| (x.as_str(), i))
+        .collect();
+
+    let (mut annotation, descriptions) = io::read_annotation(annotation_path)?;
+    let ontology = match obo {
+        Some(obo) => {
+            let ontology = Ontology::new(&io::read_obo(obo)?);
+            ontology.propagate(&mut annotation);
+            Some(ontology)
+        }
+        None => None,
+    };
+
+    let mut terms: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (gene, gene_terms) in annotation.into_iter() {
+        if let Some(&i) = gene_index.get(gene.as_str()) {
+            for t in gene_terms {
+                terms.entry(t).or_default().push(i);
+            }
+        }
+    }
+    terms.retain(|_, genes| genes.len() >= *min_term_size && genes.len() <= *max_term_size);
+    if terms.is_empty() {
+        bail!(
+            "no term has between {} and {} annotated genes in the network",
+            min_term_size,
+            max_term_size
+        );
+    }
+    info!(
+        "{} terms with {}-{} genes",
+        terms.len(),
+        min_term_size,
+        max_term_size
+    );
+
+    let name = |t: &str| -> String {
+        ontology
+            .as_ref()
+            .and_then(|o| o.name(t))
+            .or_else(|| descriptions.get(t))
+            .cloned()
+            .unwrap_or_default()
+    };
+    let terms: Vec<(String, Vec<usize>)> = terms.into_iter().collect();
+
+    if let Some(folds) = cv {
+        let res: Vec<(f64, f64)> = terms
+            .par_iter()
+            .map(|(_, positives)| {
+                (
+                    predict::cross_validate(&csr, positives, *folds, *seed),
+                    predict::degree_auroc(&csr, positives),
+                )
+            })
+            .collect();
+
+        let default_path = PathBuf::from("auroc.tsv");
+        let out_path = output.unwrap_or(&default_path);
+        let mut wtr = WriterBuilder::new().delimiter(b'\t').from_path(out_path)?;
+        for ((t, positives), (auroc, degree_auroc)) in terms.iter().zip(res.iter()) {
+            wtr.serialize(AurocRecord {
+                term: t,
+                name: &name(t),
+                size: positives.len(),
+                auroc: *auroc,
+                degree_auroc: *degree_auroc,
+            })?;
+        }
+        wtr.flush()?;
+
+        let aurocs: Vec<f64> = res.iter().map(|x| x.0).filter(|x| !x.is_nan()).collect();
+        let degree: Vec<f64> = res.iter().map(|x| x.1).filter(|x| !x.is_nan()).collect();
+        if aurocs.is_empty() {
+            warn!("no term with a defined AUROC, every term needs genes outside of it");
+            return Ok(());
+        }
+        info!(
+            "{}-fold cross validation: mean AUROC {}, median AUROC {}, node degree AUROC {}",
+            folds,
+            math::mean(&aurocs),
+            math::median(&aurocs),
+            math::mean(&degree)
+        );
+        return Ok(());
+    }
+
+    // rank genes not yet annotated with each term
+    let predictions: Vec<Vec<(usize, f64)>> = terms
+        .par_iter()
+        .map(|(_, positives)| {
+            let score = predict::neighbor_voting(&csr, positives);
+            let mut is_positive = vec![false; genes.len()];
+            positives.iter().for_each(|&p| is_positive[p] = true);
+            let mut res: Vec<(usize, f64)> = score
+                .into_iter()
+                .enumerate()
+                .filter(|&(v, s)| s > 0. && !is_positive[v])
+                .collect();
+            res.sort_by(|a, b| b.1.total_cmp(&a.1));
+            res.truncate(*top.unwrap_or(&usize::MAX));
+            res
+        })
+        .collect();
+
+    let default_path = PathBuf::from("predictions.tsv");
+    let out_path = output.unwrap_or(&default_path);
+    let mut wtr = WriterBuilder::new().delimiter(b'\t').from_path(out_path)?;
+    for ((t, _), res) in terms.iter().zip(predictions.iter()) {
+        let term_name = name(t);
+        for (r, &(v, score)) in res.iter().enumerate() {
+            wtr.serialize(PredictionRecord {
+                term: t,
+                name: &term_name,
+                gene: &genes[v],
+                score,
+                rank: r + 1,
+            })?;
+        }
+    }
+    wtr.flush()?;
+
+    info!("Finish!");
+
+    Ok(())
+}
@@ -3,6 +3,7 @@ use std::{collections::HashMap, path::Path};
 use anyhow::Result;
 use csv::{Reader, Writer};
 
+use crate::id_map;
 use crate::io;
 
 pub fn parse_args(
@@ -12,7 +13,10 @@ pub fn parse_args(
     pcc_cutoff: Option<&f64>,
     rank_cutoff: Option<&f64>,
 ) -> Result<()> {
-    let gene_id_bytes = gene_id.as_bytes();
+    // the query is mapped like the network ids, e.g. stripped of an isoform suffix
+    let gene_id = id_map::map_id(gene_id);
+    let is_query =
+        |id: &[u8]| std::str::from_utf8(id).is_ok_and(|id| id_map::map_id(id) == gene_id);
     let mut rdr = Reader::from_path(input_path)?;
     let mut wtr = Writer::from_path("test")?;
 
@@ -22,7 +26,7 @@ pub fn parse_args(
     if depth == 1 {
         while rdr.read_byte_record(&mut raw_record)? {
             let record: io::ByteCsvRecord = raw_record.deserialize(Some(&headers))?;
-            if record.gene_1_bytes() != gene_id_bytes && record.gene_2_bytes() != gene_id_bytes {
+            if !is_query(record.gene_1_bytes()) && !is_query(record.gene_2_bytes()) {
                 continue;
             }
 
@@ -66,14 +70,14 @@ pub fn parse_args(
 
         let (gene_1, gene_2) = record.genes_unchecked();
 
-        graph.entry(gene_1.to_string()).or_insert(HashMap::new());
+        graph.entry(gene_1.to_string()).or_default();
         graph
             .get_mut(&gene_1)
             .unwrap()
             .entry(gene_2.to_string())
             .or_insert((record.corr(), record.rank()));
 
-        graph.entry(gene_2.to_string()).or_insert(HashMap::new());
+        graph.entry(gene_2.to_string()).or_default();
         graph
             .get_mut(&gene_2)
             .unwrap()
@@ -0,0 +1,156 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use csv::WriterBuilder;
+
+use crate::components;
+use crate::io;
+use crate::math;
+use crate::stats;
+
+#[derive(Debug, Serialize)]
+struct NetworkStats {
+    network: String,
+    nodes: usize,
+    edges: usize,
+    density: f64,
+    mean_degree: f64,
+    max_degree: usize,
+    scale_free_r2: f64,
+    scale_free_slope: f64,
+    avg_clustering: f64,
+    components: usize,
+    largest_component: usize,
+    rank_min: f64,
+    rank_q1: f64,
+    rank_median: f64,
+    rank_q3: f64,
+    rank_max: f64,
+    rank_mean: f64,
+    corr_min: f64,
+    corr_q1: f64,
+    corr_median: f64,
+    corr_q3: f64,
+    corr_max: f64,
+    corr_mean: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct NetworkReport {
+    #[serde(flatten)]
+    stats: NetworkStats,
+    degree_distribution: Vec<(usize, usize)>,
+    component_sizes: Vec<usize>,
+}
+
+fn five_numbers(list: &[f64]) -> [f64; 6] {
+    if list.is_empty() {
+        return [f64::NAN; 6];
+    }
+    [
+        math::quantile(list, 0.),
+        math::quantile(list, 0.25),
+        math::median(list),
+        math::quantile(list, 0.75),
+        math::quantile(list, 1.),
+        math::mean(list),
+    ]
+}
+
+fn network_report(input: &Path, rank_column: Option<&str>, n_bins: usize) -> Result<NetworkReport> {
+    info!("--- start read {:?} ---", input);
+    let g = io::read_graph(input, rank_column)?;
+    let csr = g.to_csr();
+    let edges = g.edges();
+
+    let n = csr.size();
+    let m = csr.n_edges();
+    let degrees: Vec<f64> = (0..n).map(|i| csr.degree(i) as f64).collect();
+
+    info!("calculate clustering coefficient...");
+    let clustering = stats::local_clustering(&csr);
+
+    info!("label connected components...");
+    let mut component_sizes = components::component_sizes(&components::connected_components(&csr));
+    component_sizes.sort_unstable_by(|a, b| b.cmp(a));
+
+    let (scale_free_r2, scale_free_slope) = if n > 0 {
+        stats::scale_free_fit(&degrees, n_bins)
+    } else {
+        (f64::NAN, f64::NAN)
+    };
+
+    let ranks: Vec<f64> = edges.iter().map(|e| e.rank()).collect();
+    let corrs: Vec<f64> = edges.iter().map(|e| e.corr()).collect();
+    let [rank_min, rank_q1, rank_median, rank_q3, rank_max, rank_mean] = five_numbers(&ranks);
+    let [corr_min, corr_q1, corr_median, corr_q3, corr_max, corr_mean] = five_numbers(&corrs);
+
+    let stats = NetworkStats {
+        network: input.to_string_lossy().to_string(),
+        nodes: n,
+        edges: m,
+        density: if n > 1 {
+            2. * m as f64 / (n * (n - 1)) as f64
+        } else {
+            0.
+        },
+        mean_degree: if n > 0 { 2. * m as f64 / n as f64 } else { 0. },
+        max_degree: (0..n).map(|i| csr.degree(i)).max().unwrap_or(0),
+        scale_free_r2,
+        scale_free_slope,
+        avg_clustering: if n > 0 { math::mean(&clustering) } else { 0. },
+        components: component_sizes.len(),
+        largest_component: component_sizes.first().cloned().unwrap_or(0),
+        rank_min,
+        rank_q1,
+        rank_median,
+        rank_q3,
+        rank_max,
+        rank_mean,
+        corr_min,
+        corr_q1,
+        corr_median,
+        corr_q3,
+        corr_max,
+        corr_mean,
+    };
+
+    Ok(NetworkReport {
+        stats,
+        degree_distribution: stats::degree_distribution(&csr),
+        component_sizes,
+    })
+}
+
+pub fn parse_args(
+    inputs: &[PathBuf],
+    output: Option<&PathBuf>,
+    rank_column: Option<&String>,
+    n_bins: &usize,
+) -> Result<()> {
+    if *n_bins < 2 {
+        bail!("--bins must be at least 2 to fit the scale-free topology");
+    }
+    let reports = inputs
+        .iter()
+        .map(|input| network_report(input, rank_column.map(|x| x.as_str()), *n_bins))
+        .collect::<Result<Vec<NetworkReport>>>()?;
+
+    let default_path = PathBuf::from("network_stats");
+    let prefix = output.unwrap_or(&default_path);
+
+    let mut wtr = WriterBuilder::new()
+        .delimiter(b'\t')
+        .from_path(prefix.with_extension("tsv"))?;
+    for report in reports.iter() {
+        wtr.serialize(&report.stats)?;
+    }
+    wtr.flush()?;
+
+    let json = std::fs::File::create(prefix.with_extension("json"))?;
+    serde_json::to_writer_pretty(json, &reports)?;
+
+    info!("Finish!");
+
+    Ok(())
+}
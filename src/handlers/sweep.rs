@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use csv::WriterBuilder;
+use ndarray_stats::*;
+
+use crate::codon;
+use crate::evaluate::{self, GoldStandard};
+use crate::graph::RankedEdge;
+use crate::io;
+use crate::rank;
+use crate::sweep;
+use crate::{CodonProfile, Normalization, Rank};
+
+#[derive(Debug, Serialize)]
+struct SweepRecord {
+    normalization: String,
+    method: String,
+    rank_cutoff: f64,
+    pcc_cutoff: Option<f64>,
+    genes: usize,
+    edges: usize,
+    codon_score: Option<f64>,
+    auroc: Option<f64>,
+    auprc: Option<f64>,
+}
+
+/// Codon usage of the sequences, prepared once for every variant.
+struct CodonReference {
+    to_fasta: Vec<Option<usize>>,
+    top: Vec<Vec<usize>>,
+    n_fasta: usize,
+    percent: f64,
+}
+
+fn evaluate_gold(edges: &[RankedEdge], genes: &[Option<usize>], gold: &GoldStandard) -> (f64, f64) {
+    let mut has_edge = vec![false; genes.len()];
+    for &(i, j, _, _) in edges.iter() {
+        has_edge[i] = true;
+        has_edge[j] = true;
+    }
+    let covered: Vec<usize> = genes
+        .iter()
+        .zip(has_edge)
+        .filter_map(|(g, e)| if e { *g } else { None })
+        .collect();
+    let positives = gold.n_positives(&covered);
+    let negatives = covered.len() * covered.len().saturating_sub(1) / 2 - positives;
+    if positives == 0 || negatives == 0 {
+        return (f64::NAN, f64::NAN);
+    }
+
+    let mut labeled: Vec<(f64, bool)> = edges
+        .iter()
+        .filter_map(|&(i, j, _, r)| Some((r, gold.is_positive(genes[i]?, genes[j]?))))
+        .collect();
+    let curve = evaluate::curve(&mut labeled);
+    (
+        evaluate::auroc(&curve, positives, negatives),
+        evaluate::auprc(&curve, positives),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn parse_args(
+    input: &Path,
+    normalizations: &[Normalization],
+    pseudo_count: &f64,
+    methods: &[Rank],
+    rank_cutoffs: &[f64],
+    pcc_cutoffs: &[f64],
+    fasta: Option<&PathBuf>,
+    percent: &f64,
+    cds_options: &codon::CdsOptions,
+    pairs: Option<&PathBuf>,
+    annotation: Option<&PathBuf>,
+    max_term_size: &usize,
+    output: Option<&PathBuf>,
+) -> Result<()> {
+    info!("--- start read {:?} ---", input);
+    let mut index: Vec<String> = vec![];
+    let raw = io::read_exp_csv(input, &mut index)?;
+
+    let codon_ref = match fasta {
+        Some(fasta) => {
+            info!("start construct codon rank matrix");
+            let (fasta_index, seqs) = io::read_cds(fasta, cds_options, None)?;
+            let codon_rank =
+                codon::make_codon_rank(&seqs, &cds_options.genetic_code, &CodonProfile::Counts)?;
+            let fasta_pos: HashMap<&str, usize> = fasta_index
+                .iter()
+                .enumerate()
+                .map(|(i, x)| (x.as_str(), i))
+                .collect();
+            let k_max = (fasta_index.len() as f64 * percent) as usize;
+            Some(CodonReference {
+                to_fasta: index
+                    .iter()
+                    .map(|g| fasta_pos.get(g.as_str()).cloned())
+                    .collect(),
+                top: (0..fasta_index.len())
+                    .map(|i| rank::top_k_by_rank(&codon_rank, i, k_max))
+                    .collect(),
+                n_fasta: fasta_index.len(),
+                percent: *percent,
+            })
+        }
+        None => None,
+    };
+
+    let gold = match (pairs, annotation) {
+        (Some(pairs), _) => Some(GoldStandard::from_pairs(&io::read_gene_pairs(pairs)?)),
+        (None, Some(annotation)) => {
+            let (annotation, _) = io::read_annotation(annotation)?;
+            Some(GoldStandard::from_annotation(&annotation, *max_term_size))
+        }
+        (None, None) => None,
+    };
+    let gold_genes: Vec<Option<usize>> = match gold.as_ref() {
+        Some(gold) => index.iter().map(|g| gold.index(g)).collect(),
+        None => vec![],
+    };
+
+    let max_rank = rank_cutoffs.iter().cloned().fold(0., f64::max);
+    let pcc_cutoffs: Vec<Option<f64>> = if pcc_cutoffs.is_empty() {
+        vec![None]
+    } else {
+        pcc_cutoffs.iter().map(|x| Some(*x)).collect()
+    };
+
+    let default_path = PathBuf::from("sweep.tsv");
+    let out_path = output.unwrap_or(&default_path);
+    let mut wtr = WriterBuilder::new().delimiter(b'\t').from_path(out_path)?;
+
+    for normalization in normalizations.iter() {
+        let mut arr = raw.clone();
+        match normalization {
+            Normalization::Raw => {}
+            Normalization::Log2 => arr.par_mapv_inplace(|x| (x + pseudo_count).log2()),
+            Normalization::Rank => sweep::rank_transform_rows(&mut arr),
+        }
+        info!(
+            "{}: calculate correlation and rank matrix...",
+            normalization
+        );
+        let corr = arr.pearson_correlation()?;
+        let rank_arr = rank::construct_rank_matrix_multithreading(&corr, index.len())?;
+
+        for method in methods.iter() {
+            let candidates = sweep::ranked_edges(&corr, &rank_arr, method, max_rank);
+            for rank_cutoff in rank_cutoffs.iter() {
+                let end = candidates.partition_point(|e| e.3 <= *rank_cutoff);
+                for pcc_cutoff in pcc_cutoffs.iter() {
+                    let edges: Vec<RankedEdge> = candidates[..end]
+                        .iter()
+                        .filter(|e| pcc_cutoff.is_none_or(|c| e.2.abs() >= c))
+                        .cloned()
+                        .collect();
+                    let mut has_edge = vec![false; index.len()];
+                    for &(i, j, _, _) in edges.iter() {
+                        has_edge[i] = true;
+                        has_edge[j] = true;
+                    }
+                    let genes = has_edge.iter().filter(|&&x| x).count();
+
+                    let codon_score = codon_ref.as_ref().map(|c| {
+                        let k = (std::cmp::min(genes, c.n_fasta) as f64 * c.percent) as usize;
+                        sweep::codon_score(&edges, index.len(), &c.to_fasta, &c.top, k)
+                    });
+                    let (auroc, auprc) = match gold.as_ref() {
+                        Some(gold) => {
+                            let (auroc, auprc) = evaluate_gold(&edges, &gold_genes, gold);
+                            (Some(auroc), Some(auprc))
+                        }
+                        None => (None, None),
+                    };
+
+                    let record = SweepRecord {
+                        normalization: normalization.to_string(),
+                        method: method.to_string(),
+                        rank_cutoff: *rank_cutoff,
+                        pcc_cutoff: *pcc_cutoff,
+                        genes,
+                        edges: edges.len(),
+                        codon_score,
+                        auroc,
+                        auprc,
+                    };
+                    info!("{:?}", record);
+                    wtr.serialize(record)?;
+                }
+            }
+        }
+    }
+    wtr.flush()?;
+
+    info!("Finish!");
+
+    Ok(())
+}
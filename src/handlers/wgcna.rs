@@ -0,0 +1,93 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use csv::WriterBuilder;
+use ndarray::Axis;
+
+use crate::hclust;
+use crate::io;
+use crate::wgcna;
+
+#[allow(clippy::too_many_arguments)]
+pub fn parse_args(
+    input: &Path,
+    output: Option<&PathBuf>,
+    log2: &bool,
+    pseudo_count: &f64,
+    signed: &bool,
+    power: Option<&f64>,
+    r2_cutoff: &f64,
+    min_module_size: &usize,
+    deep_split: &usize,
+    cut_height: &f64,
+    max_block_size: &usize,
+    seed: &u64,
+) -> Result<()> {
+    info!("--- start read {}  ---", input.to_str().unwrap());
+    info!("log2 transform: {}, pseudo_count: {}", log2, pseudo_count);
+
+    let mut index: Vec<String> = vec![];
+    let mut arr = io::read_exp_csv(input, &mut index)?;
+    if *log2 {
+        arr.par_mapv_inplace(|x| (x + pseudo_count).log2());
+    }
+    let z = wgcna::standardize(&arr);
+
+    let default_path = PathBuf::from("wgcna");
+    let prefix = output.unwrap_or(&default_path);
+
+    info!("pick soft threshold...");
+    let table = wgcna::soft_threshold_table(&z, &wgcna::POWERS, *signed);
+    let mut wtr = WriterBuilder::new()
+        .delimiter(b'\t')
+        .from_path(prefix.with_extension("sft.tsv"))?;
+    for t in table.iter() {
+        wtr.serialize(t)?;
+    }
+    wtr.flush()?;
+
+    let power = match power {
+        Some(p) => *p,
+        None => wgcna::pick_power(&table, *r2_cutoff).unwrap_or_else(|| {
+            // the values recommended by WGCNA FAQ for networks without scale-free fit
+            let p = if *signed { 12. } else { 6. };
+            warn!(
+                "no power reaches scale-free R^2 {}, use power {}",
+                r2_cutoff, p
+            );
+            p
+        }),
+    };
+    info!("soft threshold power: {}", power);
+
+    let blocks = wgcna::blocks(&z, *max_block_size, *seed);
+    info!("{} blocks", blocks.len());
+
+    let mut labels = vec![0; index.len()];
+    let mut offset = 0;
+    for (b, block) in blocks.iter().enumerate() {
+        info!("block {}: {} genes, calculate TOM...", b + 1, block.len());
+        let zb = z.select(Axis(0), block);
+        let mut dist = wgcna::tom(&zb, power, *signed);
+        dist.mapv_inplace(|t| 1. - t);
+
+        info!("block {}: hierarchical clustering...", b + 1);
+        let merges = hclust::average_linkage(dist);
+        let block_labels =
+            hclust::dynamic_tree_cut(&merges, *cut_height, *min_module_size, *deep_split);
+
+        for (&i, &l) in block.iter().zip(block_labels.iter()) {
+            if l > 0 {
+                labels[i] = l + offset;
+            }
+        }
+        offset += block_labels.iter().max().cloned().unwrap_or(0);
+    }
+    info!("{} modules", offset);
+
+    io::write_cluster_table(prefix.with_extension("modules.tsv"), &index, &labels)?;
+
+    info!("Finish!");
+
+    Ok(())
+}
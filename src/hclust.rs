@@ -0,0 +1,248 @@
+use ndarray::Array2;
+
+use crate::components::UnionFind;
+
+/// One merge of a dendrogram. Like scipy's linkage matrix, leaves are `0..n` and the
+/// cluster formed by the `t`-th merge is `n + t`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Merge {
+    pub left: usize,
+    pub right: usize,
+    pub height: f64,
+    pub size: usize,
+}
+
+/// Average linkage (UPGMA) hierarchical clustering of a symmetric distance matrix by the
+/// nearest-neighbor chain algorithm. O(n^2) time and no extra memory beyond `dist`,
+/// which is overwritten.
+pub fn average_linkage(mut dist: Array2<f64>) -> Vec<Merge> {
+    let n = dist.nrows();
+    let mut size = vec![1usize; n];
+    let mut active = vec![true; n];
+    let mut chain: Vec<usize> = Vec::with_capacity(n);
+    // (representative a, representative b, height), in the order they are found
+    let mut raw: Vec<(usize, usize, f64)> = Vec::with_capacity(n.saturating_sub(1));
+
+    for _ in 1..n {
+        if chain.is_empty() {
+            chain.push(active.iter().position(|&a| a).unwrap());
+        }
+
+        let (a, b, h) = loop {
+            let a = *chain.last().unwrap();
+            let prev = if chain.len() > 1 {
+                Some(chain[chain.len() - 2])
+            } else {
+                None
+            };
+
+            // prefer the previous element of the chain on ties so the chain terminates
+            let mut best = prev.unwrap_or(usize::MAX);
+            let mut best_dist = prev.map_or(f64::INFINITY, |p| dist[[a, p]]);
+            for x in 0..n {
+                if active[x] && x != a && dist[[a, x]] < best_dist {
+                    best = x;
+                    best_dist = dist[[a, x]];
+                }
+            }
+
+            if Some(best) == prev {
+                chain.pop();
+                chain.pop();
+                break (a, best, best_dist);
+            }
+            chain.push(best);
+        };
+
+        // Lance-Williams update for average linkage, the merged cluster is kept at `b`
+        raw.push((a, b, h));
+        let (sa, sb) = (size[a] as f64, size[b] as f64);
+        for x in 0..n {
+            if active[x] && x != a && x != b {
+                let d = (sa * dist[[a, x]] + sb * dist[[b, x]]) / (sa + sb);
+                dist[[b, x]] = d;
+                dist[[x, b]] = d;
+            }
+        }
+        active[a] = false;
+        size[b] += size[a];
+    }
+
+    // sort by height and convert representatives into cluster ids
+    raw.sort_by(|x, y| x.2.total_cmp(&y.2));
+    let mut uf = UnionFind::new(n);
+    let mut cluster_id: Vec<usize> = (0..n).collect();
+    let mut cluster_size = vec![1usize; n];
+    raw.iter()
+        .enumerate()
+        .map(|(t, &(a, b, height))| {
+            let (ra, rb) = (uf.find(a), uf.find(b));
+            let (left, right) = (cluster_id[ra], cluster_id[rb]);
+            let size = cluster_size[ra] + cluster_size[rb];
+            uf.union(ra, rb);
+            let root = uf.find(ra);
+            cluster_id[root] = n + t;
+            cluster_size[root] = size;
+            Merge {
+                left: std::cmp::min(left, right),
+                right: std::cmp::max(left, right),
+                height,
+                size,
+            }
+        })
+        .collect()
+}
+
+/// Top-down branch splitting after a static cut, after the idea of the dynamic tree cut of
+/// Langfelder et al. (2008). It is not a port of `cutreeDynamic` or `cutreeDynamicTree`
+/// from the dynamicTreeCut R package, and its modules will not match theirs.
+///
+/// The dendrogram is first cut at `cut_height`. Each resulting branch is then split at its
+/// top merge when both children have at least `min_size` leaves and both are compact:
+/// the mean merge height inside each child ("core scatter") lies at least `min_gap` below
+/// the height that joins them. `min_gap` is a fraction of the height range between the
+/// lowest merge and `cut_height`: 0.36, 0.27, 0.18, 0.09 and 0.05 for `deep_split` 0 to 4.
+/// These fractions are this crate's own choice, not the package's. Unlike the hybrid
+/// method, leaves left out of a module are not reassigned to the nearest module.
+///
+/// Returns a module label per leaf: 1, 2, ... by decreasing size, 0 for unassigned leaves.
+pub fn dynamic_tree_cut(
+    merges: &[Merge],
+    cut_height: f64,
+    min_size: usize,
+    deep_split: usize,
+) -> Vec<usize> {
+    let n = merges.len() + 1;
+    let total = n + merges.len();
+    let mut size = vec![1usize; total];
+    let mut height_sum = vec![0.; total];
+    let mut height_count = vec![0usize; total];
+    let mut height = vec![0.; total];
+    for (t, m) in merges.iter().enumerate() {
+        let c = n + t;
+        size[c] = m.size;
+        height[c] = m.height;
+        height_sum[c] = height_sum[m.left] + height_sum[m.right] + m.height;
+        height_count[c] = height_count[m.left] + height_count[m.right] + 1;
+    }
+    let core = |c: usize| {
+        if height_count[c] == 0 {
+            0.
+        } else {
+            height_sum[c] / height_count[c] as f64
+        }
+    };
+
+    let min_height = merges
+        .iter()
+        .map(|m| m.height)
+        .fold(f64::INFINITY, f64::min);
+    let factor = [0.64, 0.73, 0.82, 0.91, 0.95][std::cmp::min(deep_split, 4)];
+    let min_gap = (1. - factor) * (cut_height - min_height).max(0.);
+
+    // branches below the cut height are the starting points
+    let mut stack: Vec<usize> = Vec::new();
+    let mut roots = vec![total - 1];
+    while let Some(c) = roots.pop() {
+        if c >= n && height[c] > cut_height {
+            let m = merges[c - n];
+            roots.push(m.left);
+            roots.push(m.right);
+        } else {
+            stack.push(c);
+        }
+    }
+
+    let mut modules: Vec<usize> = Vec::new();
+    while let Some(c) = stack.pop() {
+        if size[c] < min_size {
+            continue;
+        }
+        if c >= n {
+            let m = merges[c - n];
+            let distinct = |x: usize| size[x] >= min_size && core(x) <= m.height - min_gap;
+            if distinct(m.left) && distinct(m.right) {
+                stack.push(m.left);
+                stack.push(m.right);
+                continue;
+            }
+        }
+        modules.push(c);
+    }
+
+    // leaves of each module
+    let leaves_of = |c: usize| {
+        let mut leaves = vec![];
+        let mut todo = vec![c];
+        while let Some(x) = todo.pop() {
+            if x < n {
+                leaves.push(x);
+            } else {
+                todo.push(merges[x - n].left);
+                todo.push(merges[x - n].right);
+            }
+        }
+        leaves
+    };
+    let mut module_leaves: Vec<Vec<usize>> = modules.into_iter().map(leaves_of).collect();
+    module_leaves.sort_by(|a, b| {
+        b.len()
+            .cmp(&a.len())
+            .then(a.iter().min().cmp(&b.iter().min()))
+    });
+
+    let mut labels = vec![0; n];
+    for (i, leaves) in module_leaves.iter().enumerate() {
+        for &l in leaves {
+            labels[l] = i + 1;
+        }
+    }
+    labels
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_average_linkage_1() {
+        // points on a line: 0, 1, 5, 6, 20
+        let x = [0., 1., 5., 6., 20.];
+        let dist = Array2::from_shape_fn((5, 5), |(i, j)| f64::abs(x[i] - x[j]));
+        let merges = average_linkage(dist);
+
+        assert_eq!(merges.len(), 4);
+        assert_eq!((merges[0].left, merges[0].right, merges[0].size), (0, 1, 2));
+        assert_eq!((merges[1].left, merges[1].right, merges[1].size), (2, 3, 2));
+        assert_abs_diff_eq!(merges[1].height, 1.);
+        // {0, 1} and {5, 6}: mean of 5, 6, 4, 5
+        assert_eq!((merges[2].left, merges[2].right), (5, 6));
+        assert_abs_diff_eq!(merges[2].height, 5.);
+        assert_eq!((merges[3].left, merges[3].right, merges[3].size), (4, 7, 5));
+        assert_abs_diff_eq!(merges[3].height, 17.);
+    }
+
+    #[test]
+    fn test_dynamic_tree_cut_1() {
+        // two tight groups of three and a far outlier
+        let dist = array![
+            [0.0, 0.1, 0.1, 0.8, 0.8, 0.8, 0.99],
+            [0.1, 0.0, 0.1, 0.8, 0.8, 0.8, 0.99],
+            [0.1, 0.1, 0.0, 0.8, 0.8, 0.8, 0.99],
+            [0.8, 0.8, 0.8, 0.0, 0.1, 0.1, 0.99],
+            [0.8, 0.8, 0.8, 0.1, 0.0, 0.1, 0.99],
+            [0.8, 0.8, 0.8, 0.1, 0.1, 0.0, 0.99],
+            [0.99, 0.99, 0.99, 0.99, 0.99, 0.99, 0.0],
+        ];
+        let merges = average_linkage(dist);
+        let labels = dynamic_tree_cut(&merges, 0.9, 3, 2);
+
+        assert_eq!(labels[0..3], [labels[0]; 3]);
+        assert_eq!(labels[3..6], [labels[3]; 3]);
+        assert_ne!(labels[0], labels[3]);
+        assert!(labels[0] > 0 && labels[3] > 0);
+        assert_eq!(labels[6], 0);
+    }
+}
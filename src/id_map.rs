@@ -0,0 +1,220 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+
+use crate::io;
+
+/// Rules to harmonize gene ids across inputs, e.g. transcript ids (`AT1G01010.1`) in a FASTA
+/// against gene ids in an expression matrix.
+///
+/// An id is first stripped of the `strip` pattern, then looked up in the mapping table;
+/// ids no rule applies to are kept as they are.
+#[derive(Debug, Default)]
+pub struct IdMap {
+    table: HashMap<String, String>,
+    strip: Option<Regex>,
+    /// keep the longest sequence of FASTA records mapped to the same id
+    pub longest_isoform: bool,
+}
+
+static ID_MAP: OnceLock<IdMap> = OnceLock::new();
+
+impl IdMap {
+    /// `table` is a two column `from<TAB>to` table with a header (comma separated for `.csv`).
+    pub fn new(table: Option<&Path>, strip: Option<&str>, longest_isoform: bool) -> Result<Self> {
+        let table = match table {
+            Some(path) => io::read_two_columns(path)?.into_iter().collect(),
+            None => HashMap::new(),
+        };
+        let strip = match strip {
+            Some(pattern) => Some(
+                Regex::new(pattern).map_err(|e| anyhow!("invalid --id_strip pattern: {}", e))?,
+            ),
+            None => None,
+        };
+        Ok(Self {
+            table,
+            strip,
+            longest_isoform,
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.table.is_empty() && self.strip.is_none()
+    }
+
+    /// The mapped id, or `None` if no rule applies.
+    pub fn get<'a>(&self, id: &'a str) -> Option<Cow<'a, str>> {
+        let stripped = match self.strip.as_ref() {
+            Some(re) => re.replace(id, ""),
+            None => Cow::Borrowed(id),
+        };
+        match self.table.get(stripped.as_ref()) {
+            Some(mapped) => Some(Cow::Owned(mapped.clone())),
+            None if stripped != id => Some(stripped),
+            None => None,
+        }
+    }
+
+    /// Map `ids` in place, logging how many were mapped.
+    pub fn map_ids(&self, ids: &mut [String], source: &str) {
+        if self.is_empty() {
+            return;
+        }
+        let mut mapped = 0;
+        for id in ids.iter_mut() {
+            if let Some(m) = self.get(id) {
+                *id = m.into_owned();
+                mapped += 1;
+            }
+        }
+        info!(
+            "{}: {} ids mapped, {} unmatched kept as is",
+            source,
+            mapped,
+            ids.len() - mapped
+        );
+    }
+
+    /// Map FASTA record ids and collapse records sharing an id, keeping the longest if
+    /// `longest_isoform` is set. Shared ids are an error otherwise.
+    pub fn map_fasta(
+        &self,
+        index: Vec<String>,
+        seqs: Vec<String>,
+    ) -> Result<(Vec<String>, Vec<String>)> {
+        let mut index = index;
+        self.map_ids(&mut index, "fasta");
+        let n_records = index.len();
+
+        let mut position: HashMap<String, usize> = HashMap::new();
+        let mut kept_index: Vec<String> = vec![];
+        let mut kept_seqs: Vec<String> = vec![];
+        for (id, seq) in index.into_iter().zip(seqs) {
+            match position.get(&id) {
+                Some(&i) if self.longest_isoform => {
+                    if seq.len() > kept_seqs[i].len() {
+                        kept_seqs[i] = seq;
+                    }
+                }
+                Some(_) => {
+                    return Err(anyhow!(
+                        "several FASTA records have the id {}, use --longest_isoform to keep the longest",
+                        id
+                    ))
+                }
+                None => {
+                    position.insert(id.clone(), kept_index.len());
+                    kept_index.push(id);
+                    kept_seqs.push(seq);
+                }
+            }
+        }
+        if kept_index.len() < n_records {
+            info!(
+                "fasta: {} records collapsed to the longest isoform of {} genes",
+                n_records,
+                kept_index.len()
+            );
+        }
+        Ok((kept_index, kept_seqs))
+    }
+}
+
+/// Install the id mapping used by every reader in `io`. Called once from `main`.
+pub fn install(id_map: IdMap) {
+    if ID_MAP.set(id_map).is_err() {
+        warn!("id mapping is already installed");
+    }
+}
+
+/// The installed id mapping; an empty one maps nothing.
+pub fn global() -> &'static IdMap {
+    ID_MAP.get_or_init(IdMap::default)
+}
+
+/// Map a single id with the installed rules, for handlers streaming raw edge lists.
+pub fn map_id(id: &str) -> Cow<'_, str> {
+    global().get(id).unwrap_or(Cow::Borrowed(id))
+}
+
+/// Error on ids mapped onto the same id, where rows or nodes cannot be merged. Every clashing
+/// id is listed with the number of ids mapped onto it.
+pub fn ensure_unique(ids: &[String], source: &str) -> Result<()> {
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    for id in ids.iter() {
+        *seen.entry(id).or_insert(0) += 1;
+    }
+    let mut clashes: Vec<(&str, usize)> = seen.into_iter().filter(|&(_, n)| n > 1).collect();
+    if clashes.is_empty() {
+        return Ok(());
+    }
+    clashes.sort_unstable();
+    Err(anyhow!(
+        "{}: several ids map onto the same id: {}",
+        source,
+        clashes
+            .iter()
+            .map(|(id, n)| format!("{} ({} ids)", id, n))
+            .collect::<Vec<_>>()
+            .join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_id_map_1() {
+        let id_map = IdMap {
+            table: vec![("AT1G01020".to_string(), "ARV1".to_string())]
+                .into_iter()
+                .collect(),
+            strip: Some(Regex::new(r"\.\d+$").unwrap()),
+            longest_isoform: true,
+        };
+        assert_eq!(id_map.get("AT1G01010.1").unwrap(), "AT1G01010");
+        assert_eq!(id_map.get("AT1G01020.2").unwrap(), "ARV1");
+        assert_eq!(id_map.get("AT1G01020").unwrap(), "ARV1");
+        assert!(id_map.get("AT1G01030").is_none());
+
+        let (index, seqs) = id_map
+            .map_fasta(
+                vec!["a.1".to_string(), "b.1".to_string(), "a.2".to_string()],
+                vec!["ATG".to_string(), "ATG".to_string(), "ATGCCC".to_string()],
+            )
+            .unwrap();
+        assert_eq!(index, ["a", "b"]);
+        assert_eq!(seqs[0], "ATGCCC");
+
+        let id_map = IdMap {
+            longest_isoform: false,
+            ..id_map
+        };
+        assert!(id_map
+            .map_fasta(
+                vec!["a.1".to_string(), "a.2".to_string()],
+                vec!["ATG".to_string(), "ATG".to_string()]
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_ensure_unique_1() {
+        let ids: Vec<String> = ["a", "b", "c", "b", "a", "a"]
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+        let err = ensure_unique(&ids, "network").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "network: several ids map onto the same id: a (3 ids), b (2 ids)"
+        );
+        assert!(ensure_unique(&ids[..3], "network").is_ok());
+    }
+}
@@ -1,5 +1,5 @@
-use std::collections::HashSet;
-use std::io::{BufRead, BufReader};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::{
     fmt::{Debug, Display},
     path::Path,
@@ -7,12 +7,17 @@ use std::{
 };
 
 use flate2::read::MultiGzDecoder;
+use flate2::{Compression, GzBuilder};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use csv::{Reader, Writer};
 use ndarray::{Array2, ArrayBase};
+use rayon::prelude::*;
 
+use crate::codon;
+use crate::enrich;
 use crate::graph;
+use crate::id_map;
 use crate::math;
 
 pub fn read_exp_csv<P: AsRef<Path>>(input: P, index: &mut Vec<String>) -> Result<Array2<f64>> {
@@ -40,9 +45,51 @@ pub fn read_exp_csv<P: AsRef<Path>>(input: P, index: &mut Vec<String>) -> Result
         shape.0 += 1;
     }
     info!("shape: {:?}", shape);
+    id_map::global().map_ids(index, "expression matrix");
+    id_map::ensure_unique(index, "expression matrix")?;
     Ok(ArrayBase::from_shape_vec(shape, vec)?)
 }
 
+/// Sample names from the header of an expression matrix read by `read_exp_csv`.
+pub fn read_exp_samples<P: AsRef<Path>>(input: P) -> Result<Vec<String>> {
+    let mut rdr = Reader::from_path(input)?;
+    Ok(rdr
+        .headers()?
+        .iter()
+        .skip(1)
+        .map(|x| x.to_string())
+        .collect())
+}
+
+/// Read a numeric table with row names in the first column, e.g. a sample trait table.
+/// Empty or non numeric cells such as `NA` become NaN.
+pub fn read_numeric_csv<P: AsRef<Path>>(
+    input: P,
+) -> Result<(Vec<String>, Vec<String>, Array2<f64>)> {
+    let mut rdr = Reader::from_path(input)?;
+    let columns: Vec<String> = rdr
+        .headers()?
+        .iter()
+        .skip(1)
+        .map(|x| x.to_string())
+        .collect();
+    let mut rows = vec![];
+    let mut vec = vec![];
+
+    for _r in rdr.records() {
+        let r = _r?;
+        rows.push(r[0].to_string());
+        vec.extend(
+            r.iter()
+                .skip(1)
+                .map(|x| x.trim().parse::<f64>().unwrap_or(f64::NAN)),
+        );
+    }
+
+    let arr = ArrayBase::from_shape_vec((rows.len(), columns.len()), vec)?;
+    Ok((rows, columns, arr))
+}
+
 pub fn read_gene_list<P: AsRef<Path>>(p: &P) -> Result<HashSet<String>> {
     let mut rdr = Reader::from_path(p)?;
 
@@ -50,13 +97,15 @@ pub fn read_gene_list<P: AsRef<Path>>(p: &P) -> Result<HashSet<String>> {
 
     for _r in rdr.records() {
         let r = _r?;
-        res.insert(r.into_iter().nth(0).unwrap().to_string());
+        res.insert(r.into_iter().next().unwrap().to_string());
     }
 
-    Ok(res)
+    let mut genes: Vec<String> = res.into_iter().collect();
+    id_map::global().map_ids(&mut genes, "gene list");
+    Ok(genes.into_iter().collect())
 }
 
-fn open_with_gz<P: AsRef<Path>>(p: P) -> Result<Box<dyn BufRead>> {
+pub fn open_with_gz<P: AsRef<Path>>(p: P) -> Result<Box<dyn BufRead>> {
     let r = std::fs::File::open(p.as_ref())?;
     let ext = p.as_ref().extension();
 
@@ -70,6 +119,19 @@ fn open_with_gz<P: AsRef<Path>>(p: P) -> Result<Box<dyn BufRead>> {
     }
 }
 
+pub fn create_with_gz<P: AsRef<Path>>(p: P) -> Result<Box<dyn Write>> {
+    let w = std::fs::File::create(p.as_ref())?;
+    let ext = p.as_ref().extension();
+
+    if ext == Some(std::ffi::OsStr::new("gz")) {
+        let gz = GzBuilder::new().write(w, Compression::default());
+        Ok(Box::new(BufWriter::new(gz)))
+    } else {
+        Ok(Box::new(BufWriter::new(w)))
+    }
+}
+
+/// Read a FASTA file, mapping record ids with the installed `id_map`.
 pub fn read_fasta<P: AsRef<Path>>(fasta: P) -> Result<(Vec<String>, Vec<String>)> {
     let mut index = vec![];
     let mut seqs = vec![];
@@ -81,7 +143,7 @@ pub fn read_fasta<P: AsRef<Path>>(fasta: P) -> Result<(Vec<String>, Vec<String>)
         seqs.push(String::from_utf8(r.seq().to_vec())?);
     }
 
-    Ok((index, seqs))
+    id_map::global().map_fasta(index, seqs)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -177,6 +239,438 @@ impl ByteCsvRecord<'_> {
     }
 }
 
+/// Read an edge list (`gene_1,gene_2,corr,rank`, optionally gzipped) into a `Graph<f64>`.
+///
+/// The first two columns are taken as gene ids. `corr` is looked up by name and falls back to
+/// the third column. The rank column is `rank_column` if given, otherwise `rank`, otherwise the
+/// fourth column, so the output of `merge` can be read with `rank_column = Some("rank_mr")`,
+/// taking the corr from `corr_mr`. Without `rank_column`, a table with several rank columns
+/// and none named `rank` is an error. Self loops, duplicated pairs (in either orientation) and
+/// rows with an empty corr or rank, as left by outer joins in `merge`, are skipped.
+pub fn read_graph<P: AsRef<Path>>(path: P, rank_column: Option<&str>) -> Result<graph::Graph<f64>> {
+    let mut rdr = Reader::from_reader(open_with_gz(path.as_ref())?);
+    let headers = rdr.headers()?.clone();
+
+    let position = |name: &str| headers.iter().position(|h| h == name);
+    let corr_idx = rank_column
+        .and_then(|name| name.strip_prefix("rank_"))
+        .and_then(|label| position(&format!("corr_{}", label)))
+        .or_else(|| position("corr"))
+        .unwrap_or(2);
+    let rank_idx = match rank_column {
+        Some(name) => position(name).ok_or_else(|| anyhow!("rank column {} is not found", name))?,
+        None => position("rank").unwrap_or(3),
+    };
+    if rank_column.is_none() && position("rank").is_none() {
+        // e.g. merged networks, whose fourth column is the rank of the first input only
+        let ranks: Vec<&str> = headers
+            .iter()
+            .filter(|h| h.starts_with("rank_") || h.ends_with("_rank"))
+            .collect();
+        if ranks.len() > 1 {
+            return Err(anyhow!(
+                "{:?} has several rank columns ({}), choose one with --rank_column",
+                path.as_ref(),
+                ranks.join(", ")
+            ));
+        }
+    }
+    if headers.len() <= std::cmp::max(corr_idx, rank_idx) {
+        return Err(anyhow!("{:?} is not an edge list", path.as_ref()));
+    }
+
+    let mut nodes: Vec<String> = vec![];
+    let mut node_index: HashMap<String, usize> = HashMap::new();
+    let mut seen: HashSet<(usize, usize)> = HashSet::new();
+    let mut edges = vec![];
+    let mut skipped = 0;
+    let mut missing = 0;
+
+    let mut intern = |name: &str, nodes: &mut Vec<String>| -> usize {
+        if let Some(&i) = node_index.get(name) {
+            return i;
+        }
+        node_index.insert(name.to_string(), nodes.len());
+        nodes.push(name.to_string());
+        nodes.len() - 1
+    };
+
+    for _r in rdr.records() {
+        let r = _r?;
+        if r[corr_idx].is_empty() || r[rank_idx].is_empty() {
+            missing += 1;
+            continue;
+        }
+        let i = intern(&r[0], &mut nodes);
+        let j = intern(&r[1], &mut nodes);
+        let corr: f64 = r[corr_idx].parse()?;
+        let rank: f64 = r[rank_idx].parse()?;
+
+        if i == j || !seen.insert((std::cmp::min(i, j), std::cmp::max(i, j))) {
+            skipped += 1;
+            continue;
+        }
+        edges.push(graph::Edge::new(i, j, corr, rank));
+    }
+
+    if skipped > 0 {
+        warn!("skip {} self loops or duplicated edges", skipped);
+    }
+    if missing > 0 {
+        warn!("skip {} rows without corr or rank", missing);
+    }
+    info!("read {} nodes and {} edges", nodes.len(), edges.len());
+    map_network_ids(&mut nodes)?;
+
+    Ok(graph::Graph::from_edges(&nodes, &edges))
+}
+
+/// Map the node names of a network with the installed `id_map`. Nodes mapped onto the same
+/// id are an error, as their edges cannot be merged.
+pub fn map_network_ids(nodes: &mut [String]) -> Result<()> {
+    id_map::global().map_ids(nodes, "network");
+    id_map::ensure_unique(nodes, "network")
+}
+
+/// Gene names shared by several inputs, e.g. networks compared, merged or combined.
+#[derive(Debug, Default)]
+pub struct GeneIndex {
+    pub names: Vec<String>,
+    index: HashMap<String, usize>,
+}
+
+impl GeneIndex {
+    /// Index of `name`, adding it if new.
+    pub fn intern(&mut self, name: String) -> usize {
+        let names = &mut self.names;
+        *self.index.entry(name.clone()).or_insert_with(|| {
+            names.push(name);
+            names.len() - 1
+        })
+    }
+}
+
+/// Read an edge list as `read_graph` onto the shared `genes`. Returns the shared index of each
+/// network node and the edges on the shared index.
+pub fn read_graph_shared<P: AsRef<Path>>(
+    path: P,
+    rank_column: Option<&str>,
+    genes: &mut GeneIndex,
+) -> Result<(Vec<usize>, Vec<graph::RankedEdge>)> {
+    let g = read_graph(path, rank_column)?;
+    let to_shared: Vec<usize> = g
+        .node_names()
+        .into_iter()
+        .map(|name| genes.intern(name))
+        .collect();
+    let edges = g
+        .edges()
+        .iter()
+        .map(|e| {
+            (
+                to_shared[e.query()],
+                to_shared[e.target()],
+                e.corr(),
+                e.rank(),
+            )
+        })
+        .collect();
+    Ok((to_shared, edges))
+}
+
+pub type EdgePairs = (Vec<String>, Vec<(usize, usize)>);
+
+/// Read only the gene pairs of an edge list, for structural queries on large networks.
+///
+/// Pairs are normalised to `(min, max)`, sorted and deduplicated; self loops are dropped.
+pub fn read_edge_pairs<P: AsRef<Path>>(path: P) -> Result<EdgePairs> {
+    let mut rdr = Reader::from_reader(open_with_gz(path.as_ref())?);
+    let mut raw_record = csv::ByteRecord::new();
+
+    let mut nodes: Vec<String> = vec![];
+    let mut node_index: HashMap<Vec<u8>, usize> = HashMap::new();
+    let mut pairs = vec![];
+
+    let mut intern = |name: &[u8], nodes: &mut Vec<String>| -> Result<usize> {
+        if let Some(&i) = node_index.get(name) {
+            return Ok(i);
+        }
+        node_index.insert(name.to_vec(), nodes.len());
+        nodes.push(String::from_utf8(name.to_vec())?);
+        Ok(nodes.len() - 1)
+    };
+
+    while rdr.read_byte_record(&mut raw_record)? {
+        let i = intern(&raw_record[0], &mut nodes)?;
+        let j = intern(&raw_record[1], &mut nodes)?;
+        if i != j {
+            pairs.push((std::cmp::min(i, j), std::cmp::max(i, j)));
+        }
+    }
+
+    pairs.par_sort_unstable();
+    pairs.dedup();
+    info!("read {} nodes and {} edges", nodes.len(), pairs.len());
+    map_network_ids(&mut nodes)?;
+
+    Ok((nodes, pairs))
+}
+
+/// Write a two column `gene<TAB>cluster` table.
+pub fn write_cluster_table<P: AsRef<Path>, S: AsRef<str>>(
+    path: P,
+    genes: &[S],
+    labels: &[usize],
+) -> Result<()> {
+    let mut wtr = csv::WriterBuilder::new()
+        .delimiter(b'\t')
+        .from_path(path.as_ref())?;
+    wtr.write_record(["gene", "cluster"])?;
+    for (gene, label) in genes.iter().zip(labels) {
+        wtr.write_record([gene.as_ref(), &label.to_string()])?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Read a `gene<TAB>cluster` table with a header, as written by `write_cluster_table`.
+pub fn read_cluster_table<P: AsRef<Path>>(path: P) -> Result<Vec<(String, String)>> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(b'\t')
+        .from_reader(open_with_gz(path.as_ref())?);
+
+    let mut res = vec![];
+    for _r in rdr.records() {
+        let r = _r?;
+        if r.len() < 2 {
+            return Err(anyhow!(
+                "cluster table needs gene and cluster columns: {:?}",
+                r
+            ));
+        }
+        res.push((r[0].to_string(), r[1].to_string()));
+    }
+
+    let mut genes: Vec<String> = res.iter().map(|x| x.0.clone()).collect();
+    id_map::global().map_ids(&mut genes, "cluster table");
+
+    // a gene listed twice, e.g. isoforms collapsed by the id mapping, keeps its first cluster
+    let n_rows = res.len();
+    let mut seen: HashSet<String> = HashSet::new();
+    let res: Vec<(String, String)> = genes
+        .into_iter()
+        .zip(res)
+        .filter(|(g, _)| seen.insert(g.clone()))
+        .map(|(g, (_, c))| (g, c))
+        .collect();
+    if res.len() < n_rows {
+        warn!(
+            "{:?}: keep the first of {} rows of duplicated genes",
+            path.as_ref(),
+            n_rows - res.len()
+        );
+    }
+    Ok(res)
+}
+
+/// Read the first two columns of a table with a header, comma separated for `.csv` files
+/// and tab separated otherwise.
+pub fn read_two_columns<P: AsRef<Path>>(path: P) -> Result<Vec<(String, String)>> {
+    let delimiter = if path.as_ref().to_string_lossy().contains(".csv") {
+        b','
+    } else {
+        b'\t'
+    };
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .from_reader(open_with_gz(path.as_ref())?);
+
+    let mut res = vec![];
+    for _r in rdr.records() {
+        let r = _r?;
+        if r.len() < 2 {
+            return Err(anyhow!("table needs two columns: {:?}", r));
+        }
+        res.push((r[0].to_string(), r[1].to_string()));
+    }
+
+    Ok(res)
+}
+
+/// Read the `column` of a sample metadata table with a header and sample names in the first
+/// column, comma separated for `.csv` files and tab separated otherwise.
+pub fn read_sample_column<P: AsRef<Path>>(path: P, column: &str) -> Result<Vec<(String, String)>> {
+    let delimiter = if path.as_ref().to_string_lossy().contains(".csv") {
+        b','
+    } else {
+        b'\t'
+    };
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .from_reader(open_with_gz(path.as_ref())?);
+    let idx = rdr
+        .headers()?
+        .iter()
+        .position(|h| h == column)
+        .ok_or_else(|| anyhow!("column {} is not found in {:?}", column, path.as_ref()))?;
+
+    let mut res = vec![];
+    for _r in rdr.records() {
+        let r = _r?;
+        res.push((r[0].to_string(), r[idx].to_string()));
+    }
+
+    Ok(res)
+}
+
+/// Read gene pairs from the first two columns of a table, as `read_two_columns`.
+pub fn read_gene_pairs<P: AsRef<Path>>(path: P) -> Result<Vec<(String, String)>> {
+    let pairs = read_two_columns(path)?;
+    let mut genes: Vec<String> = pairs.into_iter().flat_map(|(a, b)| vec![a, b]).collect();
+    id_map::global().map_ids(&mut genes, "gene pairs");
+    Ok(genes
+        .chunks_exact(2)
+        .map(|x| (x[0].clone(), x[1].clone()))
+        .collect())
+}
+
+/// Read gene to term annotation, either a GAF file (gene from column 2, term from column 5,
+/// `NOT` qualifiers skipped) or a tab separated `gene<TAB>term[<TAB>description]` table.
+///
+/// Returns the annotation and the term descriptions found in the table.
+pub fn read_annotation<P: AsRef<Path>>(
+    path: P,
+) -> Result<(enrich::Annotation, HashMap<String, String>)> {
+    let gaf_name = path.as_ref().to_string_lossy().contains(".gaf");
+    let mut annotation: enrich::Annotation = HashMap::new();
+    let mut descriptions: HashMap<String, String> = HashMap::new();
+
+    for (i, line) in open_with_gz(path.as_ref())?.lines().enumerate() {
+        let line = line?;
+        let gaf = gaf_name || line.starts_with("!gaf-version");
+        if line.is_empty() || line.starts_with('!') || line.starts_with('#') {
+            continue;
+        }
+        let cols: Vec<&str> = line.split('\t').collect();
+        let (gene, term) = if gaf {
+            if cols.len() < 5 {
+                return Err(anyhow!("line {} is not a GAF record", i + 1));
+            }
+            if cols[3].contains("NOT") {
+                continue;
+            }
+            (cols[1], cols[4])
+        } else {
+            if cols.len() < 2 {
+                return Err(anyhow!("line {} needs gene and term columns", i + 1));
+            }
+            if let Some(d) = cols.get(2) {
+                descriptions.insert(cols[1].to_string(), d.to_string());
+            }
+            (cols[0], cols[1])
+        };
+        annotation
+            .entry(gene.to_string())
+            .or_default()
+            .insert(term.to_string());
+    }
+
+    let id_map = id_map::global();
+    let (mut genes, terms): (Vec<String>, Vec<_>) = annotation.into_iter().unzip();
+    id_map.map_ids(&mut genes, "annotation");
+    let mut annotation: enrich::Annotation = HashMap::new();
+    for (gene, terms) in genes.into_iter().zip(terms) {
+        annotation.entry(gene).or_default().extend(terms);
+    }
+
+    Ok((annotation, descriptions))
+}
+
+/// Read the `[Term]` stanzas of an OBO file.
+pub fn read_obo<P: AsRef<Path>>(path: P) -> Result<Vec<enrich::OboTerm>> {
+    let mut terms = vec![];
+    let mut current: Option<enrich::OboTerm> = None;
+
+    for line in open_with_gz(path.as_ref())?.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.starts_with('[') {
+            terms.extend(current.take().filter(|t| !t.id.is_empty()));
+            if line == "[Term]" {
+                current = Some(enrich::OboTerm::default());
+            }
+            continue;
+        }
+        let term = match current.as_mut() {
+            Some(t) => t,
+            None => continue,
+        };
+        let (key, value) = match line.split_once(": ") {
+            Some(x) => x,
+            None => continue,
+        };
+        // drop trailing comments such as "GO:0008150 ! biological_process"
+        let value = value.split(" ! ").next().unwrap_or("").trim();
+        match key {
+            "id" => term.id = value.to_string(),
+            "name" => term.name = value.to_string(),
+            "namespace" => term.namespace = value.to_string(),
+            "alt_id" => term.alt_ids.push(value.to_string()),
+            "is_a" => term.parents.push(value.to_string()),
+            "relationship" => {
+                if let Some(parent) = value.strip_prefix("part_of ") {
+                    term.parents.push(parent.trim().to_string());
+                }
+            }
+            "is_obsolete" => term.obsolete = value == "true",
+            _ => {}
+        }
+    }
+    terms.extend(current.filter(|t| !t.id.is_empty()));
+
+    Ok(terms)
+}
+
+/// Write records as a tab separated table with a header.
+pub fn write_tsv<P: AsRef<Path>, T: serde::Serialize>(path: P, records: &[T]) -> Result<()> {
+    let mut wtr = csv::WriterBuilder::new()
+        .delimiter(b'\t')
+        .from_path(path.as_ref())?;
+    for r in records.iter() {
+        wtr.serialize(r)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Read a FASTA of coding sequences and keep those passing `codon::validate_cds`. Skipped
+/// and trimmed sequences are logged and, if `report` is given, written there.
+pub fn read_cds<P: AsRef<Path>>(
+    fasta: P,
+    options: &codon::CdsOptions,
+    report: Option<&std::path::PathBuf>,
+) -> Result<(Vec<String>, Vec<String>)> {
+    let (index, seqs) = read_fasta(fasta)?;
+    info!(
+        "genetic code {}: {}",
+        options.genetic_code.id, options.genetic_code.name
+    );
+    let (index, seqs, cds_report) = codon::filter_cds(&index, &seqs, options);
+    let skipped = cds_report.iter().filter(|r| r.status == "skipped").count();
+    if !cds_report.is_empty() {
+        warn!(
+            "{} sequences skipped and {} trimmed, {} kept",
+            skipped,
+            cds_report.len() - skipped,
+            index.len()
+        );
+    }
+    if let Some(report) = report {
+        write_tsv(report, &cds_report)?;
+    }
+    Ok((index, seqs))
+}
+
 pub fn graph_to_csv<P, T>(outpath: P, graph: graph::Graph<T>) -> Result<()>
 where
     P: AsRef<Path>,
@@ -192,3 +686,79 @@ where
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::*;
+
+    /// `(gene_1, gene_2, corr, rank)` of every edge of a network.
+    fn edge_list(g: &graph::Graph<f64>) -> Vec<(String, String, f64, f64)> {
+        let names = g.node_names();
+        g.edges()
+            .iter()
+            .map(|e| {
+                (
+                    names[e.query()].clone(),
+                    names[e.target()].clone(),
+                    e.corr(),
+                    e.rank(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_read_cluster_table_1() {
+        // g1 is listed twice, the first cluster is kept
+        let clusters = read_cluster_table("test/clusters_duplicated.tsv").unwrap();
+        assert_eq!(
+            clusters,
+            [("g1", "c1"), ("g2", "c1"), ("g3", "c2")]
+                .iter()
+                .map(|(g, c)| (g.to_string(), c.to_string()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_read_graph_merged_1() {
+        // rank_<label> columns of merge, with empty cells left by an outer join
+        let g = read_graph("test/merged_outer_graph.csv", Some("rank_mr")).unwrap();
+        let edges = edge_list(&g);
+        assert_eq!(edges.len(), 3);
+        assert_eq!(g.size(), 5);
+        let (_, _, corr, rank) = edges.iter().find(|e| e.0 == "3").unwrap();
+        assert_abs_diff_eq!(*corr, 0.6207092962902666);
+        assert_abs_diff_eq!(*rank, 4.);
+
+        let g = read_graph("test/merged_outer_graph.csv", Some("rank_hrr")).unwrap();
+        let edges = edge_list(&g);
+        assert_eq!(edges.len(), 3);
+        assert!(edges.iter().all(|e| e.0 != "3"));
+        assert!(edges.iter().any(|e| (e.0.as_str(), e.3) == ("1", 1.)));
+    }
+
+    #[test]
+    fn test_read_graph_merged_2() {
+        // legacy corr,hrr_rank,mr_rank layout
+        let g = read_graph("test/merged_graph.csv", Some("mr_rank")).unwrap();
+        let edges = edge_list(&g);
+        assert_eq!(edges.len(), 9);
+        let (_, _, corr, rank) = edges
+            .iter()
+            .find(|e| (e.0.as_str(), e.1.as_str()) == ("1", "9"))
+            .unwrap();
+        assert_abs_diff_eq!(*corr, 0.5580918453539513);
+        assert_abs_diff_eq!(*rank, 3.1622776601683795);
+
+        let g = read_graph("test/merged_graph_missing.csv", Some("mr_rank")).unwrap();
+        assert_eq!(edge_list(&g).len(), 2);
+        let g = read_graph("test/merged_graph_missing.csv", Some("hrr_rank")).unwrap();
+        assert_eq!(edge_list(&g).len(), 3);
+
+        // several rank columns need --rank_column
+        assert!(read_graph("test/merged_outer_graph.csv", None).is_err());
+        assert!(read_graph("test/merged_graph.csv", None).is_err());
+    }
+}
@@ -20,13 +20,40 @@ use std::path::PathBuf;
 use anyhow::Result;
 use structopt::{clap, clap::arg_enum, StructOpt};
 
+mod centrality;
 mod codon;
+mod community;
+mod compare;
+mod components;
+mod consensus;
+mod diffcoex;
+mod eigengene;
+mod enrich;
+mod evaluate;
+mod genetic_code;
 mod graph;
 mod handlers;
+mod hclust;
+mod id_map;
 mod io;
 mod math;
+mod mcl;
+mod merge;
+mod null;
+mod partition;
+mod predict;
+mod quality;
 mod rank;
 mod similarity;
+mod stats;
+mod sweep;
+mod wgcna;
+
+pub use codon::CodonProfile;
+pub use consensus::Aggregation;
+pub use merge::Join;
+pub use rank::{Rank, Weight};
+pub use similarity::RankMetric;
 
 #[derive(Debug, StructOpt)]
 #[structopt(name = "corrnet")]
@@ -35,6 +62,21 @@ mod similarity;
 pub struct Opt {
     #[structopt(long = "log", possible_values(&LogLevel::variants()))]
     pub log_level: Option<LogLevel>,
+    #[structopt(
+        long = "id_map",
+        help = "two column table mapping input ids to gene ids"
+    )]
+    pub id_map: Option<PathBuf>,
+    #[structopt(
+        long = "id_strip",
+        help = "regex removed from input ids, e.g. '\\.\\d+$' for transcript versions"
+    )]
+    pub id_strip: Option<String>,
+    #[structopt(
+        long = "longest_isoform",
+        help = "keep the longest of FASTA records mapped to the same gene"
+    )]
+    pub longest_isoform: bool,
     #[structopt(subcommand)]
     pub subcommand: SubCommands,
 }
@@ -51,9 +93,40 @@ arg_enum! {
 
 arg_enum! {
     #[derive(Debug)]
-    pub enum Rank {
-        HRR,
-        MR,
+    pub enum Normalization {
+        Raw,
+        Log2,
+        Rank,
+    }
+}
+
+arg_enum! {
+    #[derive(Debug)]
+    pub enum NullModel {
+        Permutation,
+        Rewiring,
+    }
+}
+
+arg_enum! {
+    #[derive(Debug)]
+    pub enum Clustering {
+        HCCA,
+        Louvain,
+        Leiden,
+        MCL,
+    }
+}
+
+arg_enum! {
+    #[derive(Debug)]
+    pub enum Centrality {
+        Degree,
+        WeightedDegree,
+        Betweenness,
+        Closeness,
+        Eigenvector,
+        PageRank,
     }
 }
 
@@ -94,15 +167,91 @@ pub enum SubCommands {
         #[structopt(long = "pcc_cutoff")]
         pcc_cutoff: Option<f64>,
     },
-    #[structopt(name = "codon-usage", about = "calculate codon score")]
+    #[structopt(
+        name = "codon-usage",
+        about = "calculate codon score, or COSMIX against any per-gene feature matrix"
+    )]
     #[structopt(setting(clap::AppSettings::ColoredHelp))]
     CodonUsage {
         #[structopt(short = "-i", long = "input_graph")]
         input_graph: PathBuf,
-        #[structopt(short = "-f", long = "input_fasta")]
-        input_fasta: PathBuf,
+        #[structopt(
+            short = "-f",
+            long = "input_fasta",
+            required_unless = "features",
+            conflicts_with = "features"
+        )]
+        input_fasta: Option<PathBuf>,
+        #[structopt(
+            long = "features",
+            help = "per-gene feature matrix (csv, genes in rows) as reference instead of codon usage"
+        )]
+        features: Option<PathBuf>,
         #[structopt(short = "-p", long = "percent", default_value = "0.1")]
         percent: f64,
+        #[structopt(
+            short = "-k",
+            long = "k",
+            use_delimiter = true,
+            help = "top k values to score, instead of --percent"
+        )]
+        ks: Vec<usize>,
+        #[structopt(long = "curve", help = "table of the score at every k")]
+        curve: Option<PathBuf>,
+        #[structopt(long = "metric", possible_values(&RankMetric::variants()), default_value = "COSMIX")]
+        metric: RankMetric,
+        #[structopt(
+            long = "rbo_p",
+            default_value = "0.9",
+            help = "persistence of RBO, smaller weights the top more"
+        )]
+        rbo_p: f64,
+        #[structopt(
+            long = "profile",
+            possible_values(&CodonProfile::variants()),
+            default_value = "Counts",
+            help = "codon usage compared between genes"
+        )]
+        profile: CodonProfile,
+        #[structopt(
+            long = "min_cds_length",
+            default_value = "30",
+            help = "skip shorter CDS (nt)"
+        )]
+        min_cds_length: usize,
+        #[structopt(
+            long = "require_start",
+            help = "skip CDS not starting with a start codon"
+        )]
+        require_start: bool,
+        #[structopt(
+            long = "genetic-code",
+            default_value = "1",
+            help = "NCBI translation table ID"
+        )]
+        genetic_code: u32,
+        #[structopt(long = "cds_report", help = "table of skipped and trimmed sequences")]
+        cds_report: Option<PathBuf>,
+        #[structopt(short = "-o", long = "output", help = "per-gene COSMIX table")]
+        output: Option<PathBuf>,
+        #[structopt(long = "bins", default_value = "10", help = "histogram bins")]
+        bins: usize,
+        #[structopt(
+            long = "permutations",
+            default_value = "0",
+            help = "null model replicates for z-score and empirical p-value"
+        )]
+        permutations: usize,
+        #[structopt(long = "null", possible_values(&NullModel::variants()), default_value = "Permutation")]
+        null_model: NullModel,
+        #[structopt(
+            long = "swaps",
+            default_value = "10",
+            help = "edge swaps per edge for Rewiring"
+        )]
+        swaps: usize,
+        #[structopt(long = "seed", default_value = "0")]
+        seed: u64,
     },
     #[structopt(name = "query", about = "query")]
     #[structopt(setting(clap::AppSettings::ColoredHelp))]
@@ -118,20 +267,563 @@ pub enum SubCommands {
         #[structopt(long = "pcc_cutoff")]
         pcc_cutoff: Option<f64>,
     },
-    #[structopt(name = "merge", about = "merge network")]
+    #[structopt(name = "merge", about = "join labelled edge lists by gene pair")]
     #[structopt(setting(clap::AppSettings::ColoredHelp))]
     Merge {
-        #[structopt(long = "hrr")]
-        hrr_path: PathBuf,
-        #[structopt(long = "mr")]
-        mr_path: PathBuf,
+        #[structopt(
+            short = "-i",
+            long = "input",
+            required = true,
+            help = "label=path of each edge list, e.g. hrr=hrr.csv.gz"
+        )]
+        inputs: Vec<String>,
+        #[structopt(long = "rank_column")]
+        rank_column: Option<String>,
+        #[structopt(long = "join", possible_values(&Join::variants()), default_value = "Inner")]
+        join: Join,
         #[structopt(long = "outpath", short = "-o", default_value = "merge_graph.csv.gz")]
         out_path: PathBuf,
-        #[structopt(long = "priority", possible_values(&Rank::variants()))]
-        priority: Rank,
-        #[structopt(long = "max-rank", default_value = "2000")]
+        #[structopt(
+            long = "max-rank",
+            default_value = "2000",
+            help = "drop edges of the first input above this rank, inf keeps all"
+        )]
         max_rank: f64,
     },
+    #[structopt(name = "stats", about = "summarise rank based networks")]
+    #[structopt(setting(clap::AppSettings::ColoredHelp))]
+    Stats {
+        #[structopt(short = "-i", long = "input", required = true)]
+        inputs: Vec<PathBuf>,
+        #[structopt(short = "-o", long = "output")]
+        output: Option<PathBuf>,
+        #[structopt(long = "rank_column")]
+        rank_column: Option<String>,
+        #[structopt(long = "bins", default_value = "10")]
+        n_bins: usize,
+    },
+    #[structopt(name = "centrality", about = "calculate centrality of each gene")]
+    #[structopt(setting(clap::AppSettings::ColoredHelp))]
+    Centrality {
+        #[structopt(short = "-i", long = "input")]
+        input: PathBuf,
+        #[structopt(short = "-o", long = "output")]
+        output: Option<PathBuf>,
+        #[structopt(long = "rank_column")]
+        rank_column: Option<String>,
+        #[structopt(long = "weight", possible_values(&Weight::variants()), default_value = "Decay")]
+        weight: Weight,
+        #[structopt(long = "decay", default_value = "5")]
+        decay: f64,
+        #[structopt(
+            long = "samples",
+            help = "number of sources to approximate betweenness"
+        )]
+        samples: Option<usize>,
+        #[structopt(long = "seed", default_value = "0")]
+        seed: u64,
+        #[structopt(long = "sort", possible_values(&Centrality::variants()), default_value = "Degree")]
+        sort_by: Centrality,
+        #[structopt(long = "top")]
+        top: Option<usize>,
+    },
+    #[structopt(
+        name = "components",
+        about = "label connected components and k-core of each gene"
+    )]
+    #[structopt(setting(clap::AppSettings::ColoredHelp))]
+    Components {
+        #[structopt(short = "-i", long = "input")]
+        input: PathBuf,
+        #[structopt(short = "-o", long = "output")]
+        output: Option<PathBuf>,
+        #[structopt(
+            long = "largest_component",
+            requires = "filter-output",
+            help = "keep only the largest component in --filter_output"
+        )]
+        largest_component: bool,
+        #[structopt(
+            long = "kcore",
+            requires = "filter-output",
+            help = "keep only the k-core in --filter_output"
+        )]
+        kcore: Option<usize>,
+        #[structopt(long = "filter_output")]
+        filter_output: Option<PathBuf>,
+    },
+    #[structopt(name = "clustering", about = "clustering rank based network")]
+    #[structopt(setting(clap::AppSettings::ColoredHelp))]
+    Clustering {
+        #[structopt(short = "-i", long = "input")]
+        input: PathBuf,
+        #[structopt(short = "-o", long = "output")]
+        output: Option<PathBuf>,
+        #[structopt(short = "-m", long = "method", possible_values(&Clustering::variants()), default_value = "Leiden")]
+        method: Clustering,
+        #[structopt(long = "rank_column")]
+        rank_column: Option<String>,
+        #[structopt(long = "weight", possible_values(&Weight::variants()), default_value = "Decay")]
+        weight: Weight,
+        #[structopt(long = "decay", default_value = "5")]
+        decay: f64,
+        #[structopt(long = "resolution", default_value = "1.0")]
+        resolution: f64,
+        #[structopt(long = "seed", default_value = "0")]
+        seed: u64,
+        #[structopt(long = "expansion", default_value = "2", help = "MCL expansion")]
+        expansion: usize,
+        #[structopt(long = "inflation", default_value = "2.0", help = "MCL inflation")]
+        inflation: f64,
+        #[structopt(
+            long = "prune",
+            default_value = "0.0001",
+            help = "MCL drops entries below this value"
+        )]
+        prune: f64,
+        #[structopt(
+            long = "select",
+            default_value = "1000",
+            help = "MCL keeps at most this many entries per column"
+        )]
+        select: usize,
+        #[structopt(long = "max_iter", default_value = "100", help = "MCL max iterations")]
+        max_iter: usize,
+    },
+    #[structopt(
+        name = "wgcna",
+        about = "WGCNA-style modules from soft-thresholded topological overlap"
+    )]
+    #[structopt(setting(clap::AppSettings::ColoredHelp))]
+    Wgcna {
+        #[structopt(short = "-i", long = "input")]
+        input: PathBuf,
+        #[structopt(short = "-o", long = "output", help = "output prefix")]
+        output: Option<PathBuf>,
+        #[structopt(long = "log2")]
+        log2: bool,
+        #[structopt(long = "pseudocount", default_value = "1.")]
+        pseudo_count: f64,
+        #[structopt(long = "signed")]
+        signed: bool,
+        #[structopt(
+            long = "power",
+            help = "soft threshold power, picked by scale-free fit if not given"
+        )]
+        power: Option<f64>,
+        #[structopt(long = "r2_cutoff", default_value = "0.85")]
+        r2_cutoff: f64,
+        #[structopt(long = "min_module_size", default_value = "30")]
+        min_module_size: usize,
+        #[structopt(
+            long = "deep_split",
+            default_value = "2",
+            possible_values(&["0", "1", "2", "3", "4"]),
+            help = "more, smaller modules with higher values; not the same scale as R's deepSplit"
+        )]
+        deep_split: usize,
+        #[structopt(
+            long = "cut_height",
+            default_value = "0.99",
+            help = "static cut of the 1 - TOM dendrogram before branches are split"
+        )]
+        cut_height: f64,
+        #[structopt(long = "max_block_size", default_value = "5000")]
+        max_block_size: usize,
+        #[structopt(long = "seed", default_value = "0")]
+        seed: u64,
+    },
+    #[structopt(
+        name = "eigengene",
+        about = "module eigengenes and module-trait association"
+    )]
+    #[structopt(setting(clap::AppSettings::ColoredHelp))]
+    Eigengene {
+        #[structopt(short = "-c", long = "clusters", help = "gene<TAB>cluster table")]
+        clusters: PathBuf,
+        #[structopt(short = "-i", long = "input", help = "expression matrix")]
+        input: PathBuf,
+        #[structopt(short = "-t", long = "traits", help = "sample x trait csv")]
+        traits: Option<PathBuf>,
+        #[structopt(short = "-o", long = "output", help = "output prefix")]
+        output: Option<PathBuf>,
+        #[structopt(long = "log2")]
+        log2: bool,
+        #[structopt(long = "pseudocount", default_value = "1.")]
+        pseudo_count: f64,
+    },
+    #[structopt(
+        name = "compare-clusters",
+        about = "compare two clusterings by Jaccard index, ARI, NMI and VI"
+    )]
+    #[structopt(setting(clap::AppSettings::ColoredHelp))]
+    CompareClusters {
+        #[structopt(short = "-a", long = "clusters_a", help = "gene<TAB>cluster table")]
+        clusters_a: PathBuf,
+        #[structopt(short = "-b", long = "clusters_b", help = "gene<TAB>cluster table")]
+        clusters_b: PathBuf,
+        #[structopt(short = "-o", long = "output", help = "output prefix")]
+        output: Option<PathBuf>,
+    },
+    #[structopt(
+        name = "cluster-quality",
+        about = "modularity, conductance, density, rank and silhouette of clusters"
+    )]
+    #[structopt(setting(clap::AppSettings::ColoredHelp))]
+    ClusterQuality {
+        #[structopt(
+            short = "-i",
+            long = "input",
+            help = "edge list the clusters come from"
+        )]
+        input: PathBuf,
+        #[structopt(short = "-c", long = "clusters", help = "gene<TAB>cluster table")]
+        clusters: PathBuf,
+        #[structopt(short = "-o", long = "output", help = "output prefix")]
+        output: Option<PathBuf>,
+        #[structopt(long = "rank_column")]
+        rank_column: Option<String>,
+        #[structopt(long = "weight", possible_values(&Weight::variants()), default_value = "Decay")]
+        weight: Weight,
+        #[structopt(long = "decay", default_value = "5")]
+        decay: f64,
+    },
+    #[structopt(
+        name = "enrich",
+        about = "hypergeometric enrichment of annotation terms in clusters and gene sets"
+    )]
+    #[structopt(setting(clap::AppSettings::ColoredHelp))]
+    Enrich {
+        #[structopt(
+            short = "-i",
+            long = "input",
+            help = "edge list, its genes are the background"
+        )]
+        input: PathBuf,
+        #[structopt(short = "-a", long = "annotation", help = "GAF or gene<TAB>term table")]
+        annotation: PathBuf,
+        #[structopt(short = "-c", long = "clusters", help = "gene<TAB>cluster table")]
+        clusters: Option<PathBuf>,
+        #[structopt(short = "-l", long = "gene_list")]
+        gene_lists: Vec<PathBuf>,
+        #[structopt(long = "neighborhood", help = "edge list such as query output")]
+        neighborhoods: Vec<PathBuf>,
+        #[structopt(long = "obo", help = "propagate annotation over the ontology DAG")]
+        obo: Option<PathBuf>,
+        #[structopt(short = "-o", long = "output")]
+        output: Option<PathBuf>,
+        #[structopt(long = "min_term_size", default_value = "2")]
+        min_term_size: usize,
+        #[structopt(long = "max_term_size")]
+        max_term_size: Option<usize>,
+        #[structopt(long = "padj_cutoff")]
+        padj_cutoff: Option<f64>,
+    },
+    #[structopt(
+        name = "predict",
+        about = "predict gene function by neighbor voting, or evaluate it by cross validation"
+    )]
+    #[structopt(setting(clap::AppSettings::ColoredHelp))]
+    Predict {
+        #[structopt(short = "-i", long = "input")]
+        input: PathBuf,
+        #[structopt(short = "-a", long = "annotation", help = "GAF or gene<TAB>term table")]
+        annotation: PathBuf,
+        #[structopt(long = "obo", help = "propagate annotation over the ontology DAG")]
+        obo: Option<PathBuf>,
+        #[structopt(short = "-o", long = "output")]
+        output: Option<PathBuf>,
+        #[structopt(long = "rank_column")]
+        rank_column: Option<String>,
+        #[structopt(long = "weight", possible_values(&Weight::variants()), default_value = "Decay")]
+        weight: Weight,
+        #[structopt(long = "decay", default_value = "5")]
+        decay: f64,
+        #[structopt(long = "min_term_size", default_value = "20")]
+        min_term_size: usize,
+        #[structopt(long = "max_term_size", default_value = "1000")]
+        max_term_size: usize,
+        #[structopt(
+            long = "cv",
+            help = "number of folds, write per-term AUROC instead of predictions"
+        )]
+        cv: Option<usize>,
+        #[structopt(long = "seed", default_value = "0")]
+        seed: u64,
+        #[structopt(long = "top", help = "predictions per term")]
+        top: Option<usize>,
+    },
+    #[structopt(
+        name = "evaluate",
+        about = "precision-recall and ROC of networks against gold standard gene pairs"
+    )]
+    #[structopt(setting(clap::AppSettings::ColoredHelp))]
+    Evaluate {
+        #[structopt(short = "-i", long = "input", required = true)]
+        inputs: Vec<PathBuf>,
+        #[structopt(
+            long = "rank_column",
+            help = "e.g. rank_hrr and rank_mr of a merged network"
+        )]
+        rank_columns: Vec<String>,
+        #[structopt(
+            long = "pairs",
+            conflicts_with = "annotation",
+            help = "positive gene pairs"
+        )]
+        pairs: Option<PathBuf>,
+        #[structopt(
+            short = "-a",
+            long = "annotation",
+            help = "genes sharing a term are positive"
+        )]
+        annotation: Option<PathBuf>,
+        #[structopt(long = "max_term_size", default_value = "200")]
+        max_term_size: usize,
+        #[structopt(short = "-o", long = "output", help = "output prefix")]
+        output: Option<PathBuf>,
+        #[structopt(long = "precision", default_value = "0.5,0.8", use_delimiter = true)]
+        precisions: Vec<f64>,
+        #[structopt(
+            long = "points",
+            default_value = "1000",
+            help = "points of the written curve"
+        )]
+        points: usize,
+    },
+    #[structopt(
+        name = "sweep",
+        about = "benchmark a grid of construct parameters from one correlation computation"
+    )]
+    #[structopt(setting(clap::AppSettings::ColoredHelp))]
+    Sweep {
+        #[structopt(short = "-i", long = "input")]
+        input: PathBuf,
+        #[structopt(long = "normalization", possible_values(&Normalization::variants()), default_value = "Log2", use_delimiter = true)]
+        normalizations: Vec<Normalization>,
+        #[structopt(long = "pseudocount", default_value = "1.")]
+        pseudo_count: f64,
+        #[structopt(short = "-m", long = "method", possible_values(&Rank::variants()), default_value = "HRR,MR", use_delimiter = true)]
+        methods: Vec<Rank>,
+        #[structopt(
+            long = "rank_cutoff",
+            default_value = "10,20,30,50,100",
+            use_delimiter = true
+        )]
+        rank_cutoffs: Vec<f64>,
+        #[structopt(long = "pcc_cutoff", use_delimiter = true)]
+        pcc_cutoffs: Vec<f64>,
+        #[structopt(
+            short = "-f",
+            long = "input_fasta",
+            help = "evaluate with the codon score"
+        )]
+        fasta: Option<PathBuf>,
+        #[structopt(short = "-p", long = "percent", default_value = "0.1")]
+        percent: f64,
+        #[structopt(
+            long = "min_cds_length",
+            default_value = "30",
+            help = "skip shorter CDS (nt)"
+        )]
+        min_cds_length: usize,
+        #[structopt(
+            long = "require_start",
+            help = "skip CDS not starting with a start codon"
+        )]
+        require_start: bool,
+        #[structopt(
+            long = "genetic-code",
+            default_value = "1",
+            help = "NCBI translation table ID"
+        )]
+        genetic_code: u32,
+        #[structopt(
+            long = "pairs",
+            conflicts_with = "annotation",
+            help = "positive gene pairs"
+        )]
+        pairs: Option<PathBuf>,
+        #[structopt(
+            short = "-a",
+            long = "annotation",
+            help = "genes sharing a term are positive"
+        )]
+        annotation: Option<PathBuf>,
+        #[structopt(long = "max_term_size", default_value = "200")]
+        max_term_size: usize,
+        #[structopt(short = "-o", long = "output")]
+        output: Option<PathBuf>,
+    },
+    #[structopt(
+        name = "codon-bias",
+        about = "per-gene codon bias indices: RSCU, ENC, GC3s and CAI"
+    )]
+    #[structopt(setting(clap::AppSettings::ColoredHelp))]
+    CodonBias {
+        #[structopt(short = "-f", long = "input_fasta")]
+        input_fasta: PathBuf,
+        #[structopt(
+            short = "-r",
+            long = "reference",
+            help = "highly expressed genes for CAI, one per line after a header"
+        )]
+        reference: Option<PathBuf>,
+        #[structopt(
+            long = "min_cds_length",
+            default_value = "30",
+            help = "skip shorter CDS (nt)"
+        )]
+        min_cds_length: usize,
+        #[structopt(
+            long = "require_start",
+            help = "skip CDS not starting with a start codon"
+        )]
+        require_start: bool,
+        #[structopt(
+            long = "genetic-code",
+            default_value = "1",
+            help = "NCBI translation table ID"
+        )]
+        genetic_code: u32,
+        #[structopt(long = "cds_report", help = "table of skipped and trimmed sequences")]
+        cds_report: Option<PathBuf>,
+        #[structopt(short = "-o", long = "output")]
+        output: Option<PathBuf>,
+        #[structopt(long = "rscu", help = "per-gene RSCU table")]
+        rscu: Option<PathBuf>,
+    },
+    #[structopt(
+        name = "compare",
+        about = "compare two networks: shared edges and per-gene neighborhood change"
+    )]
+    #[structopt(setting(clap::AppSettings::ColoredHelp))]
+    Compare {
+        #[structopt(short = "-a", long = "network_a")]
+        network_a: PathBuf,
+        #[structopt(short = "-b", long = "network_b")]
+        network_b: PathBuf,
+        #[structopt(long = "rank_column")]
+        rank_column: Option<String>,
+        #[structopt(
+            short = "-k",
+            long = "k",
+            default_value = "10",
+            help = "top neighbors compared per gene"
+        )]
+        k: usize,
+        #[structopt(long = "metric", possible_values(&RankMetric::variants()), default_value = "Jaccard")]
+        metric: RankMetric,
+        #[structopt(long = "rbo_p", default_value = "0.9")]
+        rbo_p: f64,
+        #[structopt(
+            long = "top",
+            default_value = "10",
+            help = "most changed genes printed"
+        )]
+        top: usize,
+        #[structopt(short = "-o", long = "output", help = "output prefix")]
+        output: Option<PathBuf>,
+    },
+    #[structopt(
+        name = "diffcoex",
+        about = "differential co-expression between two conditions and per-gene rewiring"
+    )]
+    #[structopt(setting(clap::AppSettings::ColoredHelp))]
+    DiffCoex {
+        #[structopt(
+            short = "-a",
+            long = "input_a",
+            conflicts_with = "input",
+            help = "expression matrix of condition a"
+        )]
+        input_a: Option<PathBuf>,
+        #[structopt(
+            short = "-b",
+            long = "input_b",
+            help = "expression matrix of condition b"
+        )]
+        input_b: Option<PathBuf>,
+        #[structopt(
+            short = "-i",
+            long = "input",
+            required_unless = "input-a",
+            help = "expression matrix split into conditions by --metadata"
+        )]
+        input: Option<PathBuf>,
+        #[structopt(long = "metadata", help = "sample table with a header, samples first")]
+        metadata: Option<PathBuf>,
+        #[structopt(long = "column", help = "metadata column of the conditions")]
+        column: Option<String>,
+        #[structopt(
+            long = "groups",
+            use_delimiter = true,
+            help = "the two conditions in --column, as a,b"
+        )]
+        groups: Vec<String>,
+        #[structopt(long = "log2")]
+        log2: bool,
+        #[structopt(long = "pseudocount", default_value = "1.")]
+        pseudo_count: f64,
+        #[structopt(short = "-m", long = "method", possible_values(&Rank::variants()), default_value = "HRR")]
+        method: Rank,
+        #[structopt(
+            long = "rank_cutoff",
+            default_value = "30",
+            help = "test pairs within this rank in either condition"
+        )]
+        rank_cutoff: f64,
+        #[structopt(long = "padj_cutoff", default_value = "0.05")]
+        padj_cutoff: f64,
+        #[structopt(short = "-o", long = "output", help = "output prefix")]
+        output: Option<PathBuf>,
+    },
+    #[structopt(
+        name = "consensus",
+        about = "consensus network of several datasets by rank aggregation"
+    )]
+    #[structopt(setting(clap::AppSettings::ColoredHelp))]
+    Consensus {
+        #[structopt(
+            short = "-i",
+            long = "input",
+            required_unless = "matrices",
+            help = "edge lists, e.g. HRR networks of each dataset"
+        )]
+        networks: Vec<PathBuf>,
+        #[structopt(
+            long = "samples",
+            use_delimiter = true,
+            help = "sample count of each network, to weight the combined correlation"
+        )]
+        samples: Vec<usize>,
+        #[structopt(short = "-e", long = "expression", help = "expression matrices")]
+        matrices: Vec<PathBuf>,
+        #[structopt(long = "rank_column")]
+        rank_column: Option<String>,
+        #[structopt(long = "log2")]
+        log2: bool,
+        #[structopt(long = "pseudocount", default_value = "1.")]
+        pseudo_count: f64,
+        #[structopt(short = "-m", long = "method", possible_values(&Rank::variants()), default_value = "HRR")]
+        method: Rank,
+        #[structopt(
+            long = "rank_cutoff",
+            default_value = "30",
+            help = "edges kept from each expression matrix"
+        )]
+        rank_cutoff: f64,
+        #[structopt(long = "aggregation", possible_values(&Aggregation::variants()), default_value = "MeanRank")]
+        aggregation: Aggregation,
+        #[structopt(
+            long = "min_support",
+            default_value = "1",
+            help = "datasets an edge must be found in"
+        )]
+        min_support: usize,
+        #[structopt(short = "-o", long = "output")]
+        output: Option<PathBuf>,
+    },
 }
 
 fn main() -> Result<()> {
@@ -148,6 +840,12 @@ fn main() -> Result<()> {
     };
     pretty_env_logger::init_timed();
 
+    id_map::install(id_map::IdMap::new(
+        opt.id_map.as_deref(),
+        opt.id_strip.as_deref(),
+        opt.longest_isoform,
+    )?);
+
     match &opt.subcommand {
         SubCommands::Construct {
             input,
@@ -186,9 +884,48 @@ fn main() -> Result<()> {
         SubCommands::CodonUsage {
             input_graph,
             input_fasta,
+            features,
             percent,
+            ks,
+            curve,
+            metric,
+            rbo_p,
+            profile,
+            min_cds_length,
+            require_start,
+            genetic_code,
+            cds_report,
+            output,
+            bins,
+            permutations,
+            null_model,
+            swaps,
+            seed,
         } => {
-            handlers::codon_usage::parse_args(input_graph, input_fasta, percent)?;
+            let cds_options = codon::CdsOptions {
+                min_length: *min_cds_length,
+                require_start: *require_start,
+                genetic_code: genetic_code::GeneticCode::from_ncbi(*genetic_code)?,
+            };
+            handlers::codon_usage::parse_args(
+                input_graph,
+                input_fasta.as_ref(),
+                features.as_ref(),
+                percent,
+                ks,
+                curve.as_ref(),
+                metric,
+                rbo_p,
+                profile,
+                &cds_options,
+                cds_report.as_ref(),
+                output.as_ref(),
+                bins,
+                permutations,
+                null_model,
+                swaps,
+                seed,
+            )?;
         }
         SubCommands::Query {
             gene_id,
@@ -206,13 +943,377 @@ fn main() -> Result<()> {
             )?;
         }
         SubCommands::Merge {
-            hrr_path,
-            mr_path,
+            inputs,
+            rank_column,
+            join,
             out_path,
-            priority,
             max_rank,
         } => {
-            handlers::merge::parse_args(hrr_path, mr_path, out_path, priority, max_rank)?;
+            handlers::merge::parse_args(inputs, rank_column.as_ref(), join, max_rank, out_path)?;
+        }
+        SubCommands::Stats {
+            inputs,
+            output,
+            rank_column,
+            n_bins,
+        } => {
+            handlers::stats::parse_args(inputs, output.as_ref(), rank_column.as_ref(), n_bins)?;
+        }
+        SubCommands::Centrality {
+            input,
+            output,
+            rank_column,
+            weight,
+            decay,
+            samples,
+            seed,
+            sort_by,
+            top,
+        } => {
+            handlers::centrality::parse_args(
+                input,
+                output.as_ref(),
+                rank_column.as_ref(),
+                weight,
+                decay,
+                samples.as_ref(),
+                seed,
+                sort_by,
+                top.as_ref(),
+            )?;
+        }
+        SubCommands::Components {
+            input,
+            output,
+            largest_component,
+            kcore,
+            filter_output,
+        } => {
+            handlers::components::parse_args(
+                input,
+                output.as_ref(),
+                largest_component,
+                kcore.as_ref(),
+                filter_output.as_ref(),
+            )?;
+        }
+        SubCommands::Clustering {
+            input,
+            output,
+            method,
+            rank_column,
+            weight,
+            decay,
+            resolution,
+            seed,
+            expansion,
+            inflation,
+            prune,
+            select,
+            max_iter,
+        } => {
+            let mcl_params = mcl::MclParams {
+                expansion: *expansion,
+                inflation: *inflation,
+                prune: *prune,
+                select: *select,
+                max_iter: *max_iter,
+            };
+            handlers::clustering::parse_args(
+                input,
+                output.as_ref(),
+                method,
+                rank_column.as_ref(),
+                weight,
+                decay,
+                resolution,
+                seed,
+                &mcl_params,
+            )?;
+        }
+        SubCommands::Wgcna {
+            input,
+            output,
+            log2,
+            pseudo_count,
+            signed,
+            power,
+            r2_cutoff,
+            min_module_size,
+            deep_split,
+            cut_height,
+            max_block_size,
+            seed,
+        } => {
+            handlers::wgcna::parse_args(
+                input,
+                output.as_ref(),
+                log2,
+                pseudo_count,
+                signed,
+                power.as_ref(),
+                r2_cutoff,
+                min_module_size,
+                deep_split,
+                cut_height,
+                max_block_size,
+                seed,
+            )?;
+        }
+        SubCommands::Eigengene {
+            clusters,
+            input,
+            traits,
+            output,
+            log2,
+            pseudo_count,
+        } => {
+            handlers::eigengene::parse_args(
+                clusters,
+                input,
+                traits.as_ref(),
+                output.as_ref(),
+                log2,
+                pseudo_count,
+            )?;
+        }
+        SubCommands::CompareClusters {
+            clusters_a,
+            clusters_b,
+            output,
+        } => {
+            handlers::compare_clusters::parse_args(clusters_a, clusters_b, output.as_ref())?;
+        }
+        SubCommands::ClusterQuality {
+            input,
+            clusters,
+            output,
+            rank_column,
+            weight,
+            decay,
+        } => {
+            handlers::cluster_quality::parse_args(
+                input,
+                clusters,
+                output.as_ref(),
+                rank_column.as_ref(),
+                weight,
+                decay,
+            )?;
+        }
+        SubCommands::Enrich {
+            input,
+            annotation,
+            clusters,
+            gene_lists,
+            neighborhoods,
+            obo,
+            output,
+            min_term_size,
+            max_term_size,
+            padj_cutoff,
+        } => {
+            handlers::enrich::parse_args(
+                input,
+                annotation,
+                clusters.as_ref(),
+                gene_lists,
+                neighborhoods,
+                obo.as_ref(),
+                output.as_ref(),
+                min_term_size,
+                max_term_size.as_ref(),
+                padj_cutoff.as_ref(),
+            )?;
+        }
+        SubCommands::Predict {
+            input,
+            annotation,
+            obo,
+            output,
+            rank_column,
+            weight,
+            decay,
+            min_term_size,
+            max_term_size,
+            cv,
+            seed,
+            top,
+        } => {
+            handlers::predict::parse_args(
+                input,
+                annotation,
+                obo.as_ref(),
+                output.as_ref(),
+                rank_column.as_ref(),
+                weight,
+                decay,
+                min_term_size,
+                max_term_size,
+                cv.as_ref(),
+                seed,
+                top.as_ref(),
+            )?;
+        }
+        SubCommands::Evaluate {
+            inputs,
+            rank_columns,
+            pairs,
+            annotation,
+            max_term_size,
+            output,
+            precisions,
+            points,
+        } => {
+            handlers::evaluate::parse_args(
+                inputs,
+                rank_columns,
+                pairs.as_ref(),
+                annotation.as_ref(),
+                max_term_size,
+                output.as_ref(),
+                precisions,
+                points,
+            )?;
+        }
+        SubCommands::Sweep {
+            input,
+            normalizations,
+            pseudo_count,
+            methods,
+            rank_cutoffs,
+            pcc_cutoffs,
+            fasta,
+            percent,
+            min_cds_length,
+            require_start,
+            genetic_code,
+            pairs,
+            annotation,
+            max_term_size,
+            output,
+        } => {
+            let cds_options = codon::CdsOptions {
+                min_length: *min_cds_length,
+                require_start: *require_start,
+                genetic_code: genetic_code::GeneticCode::from_ncbi(*genetic_code)?,
+            };
+            handlers::sweep::parse_args(
+                input,
+                normalizations,
+                pseudo_count,
+                methods,
+                rank_cutoffs,
+                pcc_cutoffs,
+                fasta.as_ref(),
+                percent,
+                &cds_options,
+                pairs.as_ref(),
+                annotation.as_ref(),
+                max_term_size,
+                output.as_ref(),
+            )?;
+        }
+        SubCommands::CodonBias {
+            input_fasta,
+            reference,
+            min_cds_length,
+            require_start,
+            genetic_code,
+            cds_report,
+            output,
+            rscu,
+        } => {
+            let cds_options = codon::CdsOptions {
+                min_length: *min_cds_length,
+                require_start: *require_start,
+                genetic_code: genetic_code::GeneticCode::from_ncbi(*genetic_code)?,
+            };
+            handlers::codon_bias::parse_args(
+                input_fasta,
+                reference.as_ref(),
+                &cds_options,
+                cds_report.as_ref(),
+                output.as_ref(),
+                rscu.as_ref(),
+            )?;
+        }
+        SubCommands::Compare {
+            network_a,
+            network_b,
+            rank_column,
+            k,
+            metric,
+            rbo_p,
+            top,
+            output,
+        } => {
+            handlers::compare::parse_args(
+                network_a,
+                network_b,
+                rank_column.as_ref(),
+                k,
+                metric,
+                rbo_p,
+                top,
+                output.as_ref(),
+            )?;
+        }
+        SubCommands::DiffCoex {
+            input_a,
+            input_b,
+            input,
+            metadata,
+            column,
+            groups,
+            log2,
+            pseudo_count,
+            method,
+            rank_cutoff,
+            padj_cutoff,
+            output,
+        } => {
+            handlers::diffcoex::parse_args(
+                input_a.as_ref(),
+                input_b.as_ref(),
+                input.as_ref(),
+                metadata.as_ref(),
+                column.as_ref(),
+                groups,
+                log2,
+                pseudo_count,
+                method,
+                rank_cutoff,
+                padj_cutoff,
+                output.as_ref(),
+            )?;
+        }
+        SubCommands::Consensus {
+            networks,
+            samples,
+            matrices,
+            rank_column,
+            log2,
+            pseudo_count,
+            method,
+            rank_cutoff,
+            aggregation,
+            min_support,
+            output,
+        } => {
+            handlers::consensus::parse_args(
+                networks,
+                samples,
+                matrices,
+                rank_column.as_ref(),
+                log2,
+                pseudo_count,
+                method,
+                rank_cutoff,
+                aggregation,
+                min_support,
+                output.as_ref(),
+            )?;
         }
     }
     Ok(())
@@ -1,4 +1,6 @@
 use ordered_float::OrderedFloat;
+use statrs::distribution::{ContinuousCDF, StudentsT};
+use statrs::function::factorial::ln_binomial;
 
 pub fn mean(list: &[f64]) -> f64 {
     list.iter().sum::<f64>() / list.len() as f64
@@ -28,6 +30,163 @@ pub fn median(list: &[f64]) -> f64 {
     }
 }
 
+/// Quantile with linear interpolation between closest ranks (same as numpy's default).
+pub fn quantile(list: &[f64], q: f64) -> f64 {
+    assert!(!list.is_empty());
+    assert!((0. ..=1.).contains(&q));
+    let mut v: Vec<OrderedFloat<f64>> = list.iter().map(|x| OrderedFloat::from(*x)).collect();
+    v.sort();
+
+    let pos = q * (v.len() - 1) as f64;
+    let lo = pos.floor() as usize;
+    let hi = pos.ceil() as usize;
+    v[lo].into_inner() + (v[hi].into_inner() - v[lo].into_inner()) * (pos - lo as f64)
+}
+
+/// Counts of `list` in `bins` equal-width bins over `[lo, hi]`; values outside are dropped
+/// and `hi` falls in the last bin.
+pub fn histogram(list: &[f64], bins: usize, lo: f64, hi: f64) -> Vec<usize> {
+    let mut counts = vec![0; bins];
+    let width = (hi - lo) / bins as f64;
+    for &x in list.iter().filter(|&&x| x >= lo && x <= hi) {
+        let b = std::cmp::min(((x - lo) / width) as usize, bins - 1);
+        counts[b] += 1;
+    }
+    counts
+}
+
+/// Ordinary least squares fit of `y = slope * x + intercept`. Returns `(slope, intercept, r2)`.
+pub fn linear_regression(x: &[f64], y: &[f64]) -> (f64, f64, f64) {
+    assert_eq!(x.len(), y.len());
+    let (mx, my) = (mean(x), mean(y));
+    let sxy: f64 = x.iter().zip(y).map(|(a, b)| (a - mx) * (b - my)).sum();
+    let sxx: f64 = x.iter().map(|a| (a - mx).powi(2)).sum();
+    let syy: f64 = y.iter().map(|b| (b - my).powi(2)).sum();
+
+    let slope = sxy / sxx;
+    let intercept = my - slope * mx;
+    let r2 = if syy == 0. {
+        1.
+    } else {
+        sxy * sxy / (sxx * syy)
+    };
+    (slope, intercept, r2)
+}
+
+/// Pearson correlation coefficient. Pairs where either value is NaN are skipped.
+pub fn pearson(x: &[f64], y: &[f64]) -> f64 {
+    assert_eq!(x.len(), y.len());
+    let (x, y): (Vec<f64>, Vec<f64>) = x
+        .iter()
+        .zip(y)
+        .filter(|(a, b)| !a.is_nan() && !b.is_nan())
+        .unzip();
+    let (mx, my) = (mean(&x), mean(&y));
+    let sxy: f64 = x.iter().zip(&y).map(|(a, b)| (a - mx) * (b - my)).sum();
+    let sxx: f64 = x.iter().map(|a| (a - mx).powi(2)).sum();
+    let syy: f64 = y.iter().map(|b| (b - my).powi(2)).sum();
+    sxy / (sxx * syy).sqrt()
+}
+
+/// Ranks from 1, ties getting the average of their ranks.
+pub fn average_ranks(list: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..list.len()).collect();
+    order.sort_by_key(|&i| OrderedFloat::from(list[i]));
+    let mut ranks = vec![0.; list.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && list[order[end]] == list[order[start]] {
+            end += 1;
+        }
+        let rank = (start + end + 1) as f64 / 2.;
+        order[start..end].iter().for_each(|&i| ranks[i] = rank);
+        start = end;
+    }
+    ranks
+}
+
+/// Spearman rank correlation, the Pearson correlation of average ranks.
+pub fn spearman(x: &[f64], y: &[f64]) -> f64 {
+    pearson(&average_ranks(x), &average_ranks(y))
+}
+
+/// Fisher z transform of a correlation, clamped away from +-1 to stay finite.
+pub fn fisher_z(r: f64) -> f64 {
+    r.clamp(-1. + 1e-12, 1. - 1e-12).atanh()
+}
+
+/// Two-sided p-value of a Pearson correlation `r` from `n` observations (Student's t test).
+pub fn pearson_pvalue(r: f64, n: usize) -> f64 {
+    if n < 3 || r.is_nan() {
+        return f64::NAN;
+    }
+    if r.abs() >= 1. {
+        return 0.;
+    }
+    let df = (n - 2) as f64;
+    let t = r * (df / (1. - r * r)).sqrt();
+    let dist = StudentsT::new(0., 1., df).unwrap();
+    2. * (1. - dist.cdf(t.abs()))
+}
+
+/// Area under the ROC curve by the Mann-Whitney U statistic, ties counted as half.
+/// NaN unless there are both positives and negatives.
+pub fn auroc(scores: &[f64], labels: &[bool]) -> f64 {
+    let mut order: Vec<usize> = (0..scores.len()).collect();
+    order.sort_by_key(|&i| OrderedFloat::from(scores[i]));
+
+    // sum of average ranks (1-based) of the positives
+    let (mut rank_sum, mut i) = (0., 0);
+    while i < order.len() {
+        let mut j = i;
+        while j < order.len() && scores[order[j]] == scores[order[i]] {
+            j += 1;
+        }
+        let average = (i + j + 1) as f64 / 2.;
+        rank_sum += order[i..j].iter().filter(|&&x| labels[x]).count() as f64 * average;
+        i = j;
+    }
+
+    let positives = labels.iter().filter(|&&x| x).count() as f64;
+    let negatives = labels.len() as f64 - positives;
+    (rank_sum - positives * (positives + 1.) / 2.) / (positives * negatives)
+}
+
+/// P(X >= k) for X ~ Hypergeometric(population, successes, draws), summed in log space so
+/// small tail probabilities are not lost to cancellation.
+pub fn hypergeometric_sf(k: u64, population: u64, successes: u64, draws: u64) -> f64 {
+    let lower = std::cmp::max(k, (draws + successes).saturating_sub(population));
+    let upper = std::cmp::min(successes, draws);
+    if lower > upper {
+        return 0.;
+    }
+    let total = ln_binomial(population, draws);
+    (lower..=upper)
+        .map(|i| {
+            (ln_binomial(successes, i) + ln_binomial(population - successes, draws - i) - total)
+                .exp()
+        })
+        .sum::<f64>()
+        .min(1.)
+}
+
+/// Benjamini-Hochberg adjusted p-values, in the order of `pvalues`.
+pub fn benjamini_hochberg(pvalues: &[f64]) -> Vec<f64> {
+    let n = pvalues.len();
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(OrderedFloat::from(pvalues[i])));
+
+    let mut adjusted = vec![0.; n];
+    let mut min = 1f64;
+    for (r, &i) in order.iter().enumerate() {
+        let rank = (n - r) as f64;
+        min = min.min(pvalues[i] * n as f64 / rank);
+        adjusted[i] = min;
+    }
+    adjusted
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -70,4 +229,91 @@ mod test {
         assert_abs_diff_eq!(std(&rand10, 1.), 0.265779165304154);
         assert_abs_diff_eq!(std(&rand10, 0.), 0.2521402550938575);
     }
+
+    #[test]
+    fn test_quantile_1() {
+        let v = vec![1., 2., 3., 4., 5.];
+        assert_eq!(quantile(&v, 0.), 1.);
+        assert_eq!(quantile(&v, 0.5), median(&v));
+        assert_eq!(quantile(&v, 0.25), 2.);
+        assert_eq!(quantile(&v, 1.), 5.);
+
+        let even = vec![4., 1., 3., 2.];
+        assert_abs_diff_eq!(quantile(&even, 0.25), 1.75);
+        assert_eq!(quantile(&even, 0.5), median(&even));
+    }
+
+    #[test]
+    fn test_linear_regression_1() {
+        let x = vec![1., 2., 3., 4.];
+        let y = vec![3., 5., 7., 9.];
+        let (slope, intercept, r2) = linear_regression(&x, &y);
+        assert_abs_diff_eq!(slope, 2.);
+        assert_abs_diff_eq!(intercept, 1.);
+        assert_abs_diff_eq!(r2, 1.);
+
+        let y = vec![1., 3., 2., 4.];
+        let (_, _, r2) = linear_regression(&x, &y);
+        assert_abs_diff_eq!(r2, 0.64);
+    }
+
+    #[test]
+    fn test_spearman_1() {
+        assert_eq!(average_ranks(&[3., 1., 3., 2.]), [3.5, 1., 3.5, 2.]);
+        // monotonic but not linear
+        assert_abs_diff_eq!(spearman(&[1., 2., 3., 4.], &[1., 10., 100., 1000.]), 1.);
+        assert_abs_diff_eq!(spearman(&[1., 2., 3.], &[3., 2., 1.]), -1.);
+    }
+
+    #[test]
+    fn test_pearson_1() {
+        let x = vec![1., 2., 3., 4., 5.];
+        let y = vec![2., 4., 5., 4., 5.];
+        assert_abs_diff_eq!(pearson(&x, &y), 0.7745966692414834, epsilon = 1e-12);
+        assert_abs_diff_eq!(
+            pearson(&[1., f64::NAN, 3.], &[1., 2., 3.]),
+            1.,
+            epsilon = 1e-12
+        );
+        // closed form of Student's t CDF with 3 degrees of freedom
+        assert_abs_diff_eq!(
+            pearson_pvalue(pearson(&x, &y), 5),
+            0.12402706265755459,
+            epsilon = 1e-9
+        );
+    }
+
+    #[test]
+    fn test_hypergeometric_sf_1() {
+        // (C(4, 2) C(6, 1) + C(4, 3)) / C(10, 3)
+        assert_abs_diff_eq!(hypergeometric_sf(2, 10, 4, 3), 1. / 3., epsilon = 1e-12);
+        assert_abs_diff_eq!(hypergeometric_sf(0, 10, 4, 3), 1., epsilon = 1e-12);
+        assert_eq!(hypergeometric_sf(4, 10, 4, 3), 0.);
+    }
+
+    #[test]
+    fn test_benjamini_hochberg_1() {
+        let adjusted = benjamini_hochberg(&[0.01, 0.04, 0.03, 0.2]);
+        let expected = [0.04, 0.16 / 3., 0.16 / 3., 0.2];
+        for (a, e) in adjusted.iter().zip(expected.iter()) {
+            assert_abs_diff_eq!(a, e, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_auroc_1() {
+        assert_abs_diff_eq!(
+            auroc(&[0.1, 0.4, 0.35, 0.8], &[false, false, true, true]),
+            0.75
+        );
+        // ties count as half
+        assert_abs_diff_eq!(auroc(&[1., 1., 1.], &[true, false, false]), 0.5);
+        assert!(auroc(&[1., 2.], &[true, true]).is_nan());
+    }
+
+    #[test]
+    fn test_histogram_1() {
+        let counts = histogram(&[0., 0.1, 0.25, 0.5, 0.99, 1., 1.5], 4, 0., 1.);
+        assert_eq!(counts, [2, 1, 1, 2]);
+    }
 }
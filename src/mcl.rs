@@ -0,0 +1,185 @@
+use ordered_float::OrderedFloat;
+use rayon::prelude::*;
+
+use crate::community;
+use crate::components;
+use crate::graph::Csr;
+
+/// Sparse column: `(row, value)` pairs sorted by row.
+type Column = Vec<(usize, f64)>;
+
+#[derive(Debug, Clone)]
+pub struct MclParams {
+    pub expansion: usize,
+    pub inflation: f64,
+    /// entries smaller than this are removed after each expansion
+    pub prune: f64,
+    /// at most this many largest entries are kept per column
+    pub select: usize,
+    pub max_iter: usize,
+}
+
+impl Default for MclParams {
+    fn default() -> Self {
+        Self {
+            expansion: 2,
+            inflation: 2.,
+            prune: 1e-4,
+            select: 1000,
+            max_iter: 100,
+        }
+    }
+}
+
+fn normalize(col: &mut Column) {
+    let sum: f64 = col.iter().map(|x| x.1).sum();
+    if sum > 0. {
+        col.iter_mut().for_each(|x| x.1 /= sum);
+    }
+}
+
+/// Column stochastic matrix with self loops weighted by the largest weight of each column.
+fn stochastic(csr: &Csr) -> Vec<Column> {
+    (0..csr.size())
+        .into_par_iter()
+        .map(|j| {
+            let mut col: Column = csr
+                .neighbors(j)
+                .iter()
+                .cloned()
+                .zip(csr.weights(j).iter().cloned())
+                .collect();
+            let self_loop = col.iter().map(|x| x.1).fold(1e-300, f64::max);
+            col.push((j, self_loop));
+            col.sort_by_key(|x| x.0);
+            normalize(&mut col);
+            col
+        })
+        .collect()
+}
+
+/// `left * right`, computed column by column in parallel, then pruned.
+fn multiply(left: &[Column], right: &[Column], params: &MclParams) -> Vec<Column> {
+    let n = left.len();
+    right
+        .par_iter()
+        .map_init(
+            // dense accumulator and the rows touched in it, reused per thread
+            || (vec![0.; n], Vec::new()),
+            |(acc, touched), rcol| {
+                for &(k, v) in rcol.iter() {
+                    for &(i, w) in left[k].iter() {
+                        if acc[i] == 0. {
+                            touched.push(i);
+                        }
+                        acc[i] += v * w;
+                    }
+                }
+                let mut col: Column = touched
+                    .drain(..)
+                    .map(|i| (i, std::mem::take(&mut acc[i])))
+                    .filter(|x| x.1 >= params.prune)
+                    .collect();
+                if col.len() > params.select {
+                    col.select_nth_unstable_by_key(params.select, |x| {
+                        std::cmp::Reverse(OrderedFloat::from(x.1))
+                    });
+                    col.truncate(params.select);
+                }
+                col.sort_by_key(|x| x.0);
+                normalize(&mut col);
+                col
+            },
+        )
+        .collect()
+}
+
+fn inflate(m: &mut [Column], inflation: f64) {
+    m.par_iter_mut().for_each(|col| {
+        col.iter_mut().for_each(|x| x.1 = x.1.powf(inflation));
+        normalize(col);
+    });
+}
+
+fn max_difference(a: &[Column], b: &[Column]) -> f64 {
+    a.par_iter()
+        .zip(b.par_iter())
+        .map(|(ca, cb)| {
+            // both columns are sorted by row
+            let (mut i, mut j, mut max) = (0, 0, 0f64);
+            while i < ca.len() || j < cb.len() {
+                let ra = ca.get(i).map_or(usize::MAX, |x| x.0);
+                let rb = cb.get(j).map_or(usize::MAX, |x| x.0);
+                let d = if ra == rb {
+                    i += 1;
+                    j += 1;
+                    ca[i - 1].1 - cb[j - 1].1
+                } else if ra < rb {
+                    i += 1;
+                    ca[i - 1].1
+                } else {
+                    j += 1;
+                    cb[j - 1].1
+                };
+                max = max.max(d.abs());
+            }
+            max
+        })
+        .reduce(|| 0., f64::max)
+}
+
+/// Markov clustering (van Dongen 2000) of a weighted undirected graph.
+///
+/// Clusters are the connected components of the converged flow matrix, so nodes
+/// attracted to the same attractor system share a cluster.
+pub fn mcl(csr: &Csr, params: &MclParams) -> Vec<usize> {
+    let mut m = stochastic(csr);
+
+    for iter in 0..params.max_iter {
+        let mut next = m.clone();
+        for _ in 1..params.expansion {
+            next = multiply(&m, &next, params);
+        }
+        inflate(&mut next, params.inflation);
+
+        let diff = max_difference(&m, &next);
+        debug!("mcl iteration {}: max difference {}", iter + 1, diff);
+        m = next;
+        if diff < 1e-9 {
+            break;
+        }
+    }
+
+    let pairs: Vec<(usize, usize)> = m
+        .iter()
+        .enumerate()
+        .flat_map(|(j, col)| {
+            col.iter()
+                .filter(|x| x.1 >= params.prune)
+                .map(move |&(i, _)| (i, j))
+        })
+        .collect();
+    community::relabel_by_size(&components::connected_components_from_pairs(
+        csr.size(),
+        &pairs,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_mcl_1() {
+        // two triangles joined by a single edge, and an isolated node
+        let pairs = vec![(0, 1), (0, 2), (1, 2), (2, 3), (3, 4), (3, 5), (4, 5)];
+        let csr = Csr::from_pairs(7, &pairs);
+        let labels = mcl(&csr, &MclParams::default());
+
+        assert_eq!(labels[0..3], [labels[0]; 3]);
+        assert_eq!(labels[3..6], [labels[3]; 3]);
+        assert_ne!(labels[0], labels[3]);
+        assert_ne!(labels[6], labels[0]);
+        assert_ne!(labels[6], labels[3]);
+    }
+}
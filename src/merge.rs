@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use structopt::clap::arg_enum;
+
+use crate::graph::{pair_key, RankedEdge};
+
+arg_enum! {
+    #[derive(Debug)]
+    pub enum Join {
+        Inner,
+        Outer,
+        Left,
+    }
+}
+
+/// A gene pair of merged edge lists with its corr and rank in every input, if present.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergedEdge {
+    pub gene_1: usize,
+    pub gene_2: usize,
+    pub values: Vec<Option<(f64, f64)>>,
+}
+
+/// Join edge lists on the shared gene index by gene pair, in either orientation.
+///
+/// Pairs keep the orientation and order of the first input listing them. `Inner` keeps pairs
+/// in every input, `Left` those in the first input and `Outer` all of them.
+pub fn merge_edges(inputs: &[Vec<RankedEdge>], join: &Join) -> Vec<MergedEdge> {
+    let mut position: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut merged: Vec<MergedEdge> = vec![];
+    for (n, edges) in inputs.iter().enumerate() {
+        for &(i, j, corr, rank) in edges.iter() {
+            let p = *position.entry(pair_key(i, j)).or_insert_with(|| {
+                merged.push(MergedEdge {
+                    gene_1: i,
+                    gene_2: j,
+                    values: vec![None; inputs.len()],
+                });
+                merged.len() - 1
+            });
+            // the first of duplicated pairs in an input is kept, as in `io::read_graph`
+            merged[p].values[n].get_or_insert((corr, rank));
+        }
+    }
+
+    merged.retain(|e| match join {
+        Join::Inner => e.values.iter().all(|v| v.is_some()),
+        Join::Left => e.values[0].is_some(),
+        Join::Outer => true,
+    });
+    merged
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_merge_edges_1() {
+        let hrr = vec![(0, 1, 0.9, 1.), (1, 2, 0.8, 2.)];
+        // (0, 1) swapped, (2, 3) only in mr
+        let mr = vec![(1, 0, 0.9, 1.5), (2, 3, 0.4, 8.)];
+
+        let inner = merge_edges(&[hrr.clone(), mr.clone()], &Join::Inner);
+        assert_eq!(
+            inner,
+            vec![MergedEdge {
+                gene_1: 0,
+                gene_2: 1,
+                values: vec![Some((0.9, 1.)), Some((0.9, 1.5))],
+            }]
+        );
+
+        let left = merge_edges(&[hrr.clone(), mr.clone()], &Join::Left);
+        assert_eq!(left.len(), 2);
+        assert_eq!(left[1].values, vec![Some((0.8, 2.)), None]);
+
+        let outer = merge_edges(&[hrr, mr], &Join::Outer);
+        assert_eq!(outer.len(), 3);
+        assert_eq!((outer[2].gene_1, outer[2].gene_2), (2, 3));
+        assert_eq!(outer[2].values, vec![None, Some((0.4, 8.))]);
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::HashSet;
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+
+/// Shuffle `labels` over the nodes, keeping the network structure.
+pub fn permute_labels<T: Clone>(labels: &[T], rng: &mut StdRng) -> Vec<T> {
+    let mut permuted = labels.to_vec();
+    permuted.shuffle(rng);
+    permuted
+}
+
+/// Degree-preserving rewiring by double edge swaps (Maslov and Sneppen 2002):
+/// `(a, b), (c, d)` becomes `(a, d), (c, b)`, each new edge keeping one old rank.
+/// Swaps that would create self loops or multi-edges are rejected.
+pub fn rewire(edges: &mut [(usize, usize, f64)], swaps: usize, rng: &mut StdRng) {
+    let n = edges.len();
+    if n < 2 {
+        return;
+    }
+    let key = |a: usize, b: usize| (a.min(b), a.max(b));
+    let mut present: HashSet<(usize, usize)> = edges.iter().map(|e| key(e.0, e.1)).collect();
+
+    for _ in 0..swaps {
+        let (x, y) = (rng.gen_range(0..n), rng.gen_range(0..n));
+        if x == y {
+            continue;
+        }
+        let (a, b, r1) = edges[x];
+        let (mut c, mut d, r2) = edges[y];
+        if rng.gen_bool(0.5) {
+            std::mem::swap(&mut c, &mut d);
+        }
+        if a == d || c == b || present.contains(&key(a, d)) || present.contains(&key(c, b)) {
+            continue;
+        }
+        present.remove(&key(a, b));
+        present.remove(&key(c, d));
+        present.insert(key(a, d));
+        present.insert(key(c, b));
+        edges[x] = (a, d, r1);
+        edges[y] = (c, b, r2);
+    }
+}
+
+/// Mean, standard deviation, z-score and empirical p-value (with the +1 correction) of
+/// `observed` against `null` scores.
+pub fn summarize(observed: f64, null: &[f64]) -> (f64, f64, f64, f64) {
+    let n = null.len() as f64;
+    let mean = null.iter().sum::<f64>() / n;
+    let sd = (null.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.)).sqrt();
+    let z = (observed - mean) / sd;
+    let p = (1. + null.iter().filter(|&&x| x >= observed).count() as f64) / (1. + n);
+    (mean, sd, z, p)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_rewire_1() {
+        let mut edges: Vec<(usize, usize, f64)> =
+            (0..20).map(|i| (i, (i + 1) % 20, i as f64)).collect();
+        let degree = |edges: &[(usize, usize, f64)]| {
+            let mut d = vec![0; 20];
+            edges.iter().for_each(|e| {
+                d[e.0] += 1;
+                d[e.1] += 1;
+            });
+            d
+        };
+        let before = degree(&edges);
+        let original = edges.clone();
+
+        rewire(&mut edges, 200, &mut StdRng::seed_from_u64(0));
+        assert_eq!(degree(&edges), before);
+        assert_ne!(edges, original);
+        assert!(edges.iter().all(|e| e.0 != e.1));
+        let pairs: HashSet<(usize, usize)> =
+            edges.iter().map(|e| (e.0.min(e.1), e.0.max(e.1))).collect();
+        assert_eq!(pairs.len(), 20);
+    }
+
+    #[test]
+    fn test_summarize_1() {
+        let (mean, sd, z, p) = summarize(4., &[1., 2., 3.]);
+        assert_abs_diff_eq!(mean, 2.);
+        assert_abs_diff_eq!(sd, 1.);
+        assert_abs_diff_eq!(z, 2.);
+        assert_abs_diff_eq!(p, 0.25);
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+/// Contingency table of two labelings of the same elements.
+#[derive(Debug, Clone)]
+pub struct Contingency {
+    pub counts: HashMap<(usize, usize), usize>,
+    pub a_sizes: Vec<usize>,
+    pub b_sizes: Vec<usize>,
+    pub n: usize,
+}
+
+impl Contingency {
+    /// `a` and `b` are labels in `0..k` of the same elements.
+    pub fn new(a: &[usize], b: &[usize]) -> Self {
+        assert_eq!(a.len(), b.len());
+        let mut counts = HashMap::new();
+        let mut a_sizes = vec![0; a.iter().max().map_or(0, |x| x + 1)];
+        let mut b_sizes = vec![0; b.iter().max().map_or(0, |x| x + 1)];
+        for (&i, &j) in a.iter().zip(b) {
+            *counts.entry((i, j)).or_insert(0) += 1;
+            a_sizes[i] += 1;
+            b_sizes[j] += 1;
+        }
+        Self {
+            counts,
+            a_sizes,
+            b_sizes,
+            n: a.len(),
+        }
+    }
+
+    /// Jaccard index between cluster `i` of `a` and cluster `j` of `b`.
+    pub fn jaccard(&self, i: usize, j: usize) -> f64 {
+        let inter = self.counts.get(&(i, j)).cloned().unwrap_or(0);
+        let union = self.a_sizes[i] + self.b_sizes[j] - inter;
+        if union == 0 {
+            0.
+        } else {
+            inter as f64 / union as f64
+        }
+    }
+}
+
+fn comb2(x: usize) -> f64 {
+    (x * x.saturating_sub(1)) as f64 / 2.
+}
+
+fn entropy(sizes: &[usize], n: usize) -> f64 {
+    sizes
+        .iter()
+        .filter(|&&s| s > 0)
+        .map(|&s| {
+            let p = s as f64 / n as f64;
+            -p * p.ln()
+        })
+        .sum()
+}
+
+fn mutual_information(c: &Contingency) -> f64 {
+    let n = c.n as f64;
+    c.counts
+        .iter()
+        .map(|(&(i, j), &nij)| {
+            let nij = nij as f64;
+            nij / n * (n * nij / (c.a_sizes[i] as f64 * c.b_sizes[j] as f64)).ln()
+        })
+        .sum()
+}
+
+/// Adjusted Rand index (Hubert and Arabie 1985). With fewer than 2 elements there are no
+/// pairs to disagree on, and the index is 1.
+pub fn adjusted_rand_index(c: &Contingency) -> f64 {
+    if c.n < 2 {
+        return 1.;
+    }
+    let index: f64 = c.counts.values().map(|&x| comb2(x)).sum();
+    let sum_a: f64 = c.a_sizes.iter().map(|&x| comb2(x)).sum();
+    let sum_b: f64 = c.b_sizes.iter().map(|&x| comb2(x)).sum();
+    let expected = sum_a * sum_b / comb2(c.n);
+    let max = (sum_a + sum_b) / 2.;
+    if max == expected {
+        return 1.;
+    }
+    (index - expected) / (max - expected)
+}
+
+/// Normalized mutual information, normalized by the arithmetic mean of the entropies.
+pub fn normalized_mutual_information(c: &Contingency) -> f64 {
+    let (ha, hb) = (entropy(&c.a_sizes, c.n), entropy(&c.b_sizes, c.n));
+    if ha + hb == 0. {
+        return 1.;
+    }
+    mutual_information(c) / ((ha + hb) / 2.)
+}
+
+/// Variation of information (Meila 2007) in nats.
+pub fn variation_of_information(c: &Contingency) -> f64 {
+    let (ha, hb) = (entropy(&c.a_sizes, c.n), entropy(&c.b_sizes, c.n));
+    (ha + hb - 2. * mutual_information(c)).max(0.)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::*;
+
+    #[test]
+    fn test_partition_metrics_1() {
+        let c = Contingency::new(&[0, 0, 1, 1], &[0, 0, 1, 2]);
+        assert_abs_diff_eq!(adjusted_rand_index(&c), 4. / 7.);
+        assert_abs_diff_eq!(normalized_mutual_information(&c), 0.8, epsilon = 1e-12);
+        assert_abs_diff_eq!(
+            variation_of_information(&c),
+            0.5 * 2f64.ln(),
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(c.jaccard(1, 1), 0.5);
+        assert_abs_diff_eq!(c.jaccard(0, 0), 1.);
+        assert_abs_diff_eq!(c.jaccard(0, 2), 0.);
+    }
+
+    #[test]
+    fn test_partition_metrics_2() {
+        // identical up to renaming
+        let c = Contingency::new(&[0, 0, 1, 2, 2], &[2, 2, 0, 1, 1]);
+        assert_abs_diff_eq!(adjusted_rand_index(&c), 1.);
+        assert_abs_diff_eq!(normalized_mutual_information(&c), 1., epsilon = 1e-12);
+        assert_abs_diff_eq!(variation_of_information(&c), 0., epsilon = 1e-12);
+
+        // a single shared gene
+        let c = Contingency::new(&[0], &[1]);
+        assert_abs_diff_eq!(adjusted_rand_index(&c), 1.);
+    }
+}
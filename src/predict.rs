@@ -0,0 +1,106 @@
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::graph::Csr;
+use crate::math;
+
+/// Neighbor voting score of every node: the weight of its edges to positive nodes over
+/// its total edge weight, as in EGAD (Ballouz et al. 2017).
+pub fn neighbor_voting(csr: &Csr, positives: &[usize]) -> Vec<f64> {
+    let mut score = vec![0.; csr.size()];
+    for &p in positives.iter() {
+        for (&v, w) in csr.neighbors(p).iter().zip(csr.weights(p)) {
+            score[v] += w;
+        }
+    }
+    for (v, s) in score.iter_mut().enumerate() {
+        let strength = csr.strength(v);
+        if strength > 0. {
+            *s /= strength;
+        }
+    }
+    score
+}
+
+/// Mean AUROC of neighbor voting over `folds`-fold cross validation of `positives`.
+///
+/// In each fold the held out positives are ranked against every node not annotated with
+/// the term; the training positives are left out of the ranking.
+pub fn cross_validate(csr: &Csr, positives: &[usize], folds: usize, seed: u64) -> f64 {
+    let mut shuffled = positives.to_vec();
+    shuffled.shuffle(&mut StdRng::seed_from_u64(seed));
+    let folds = folds.clamp(2, std::cmp::max(shuffled.len(), 2));
+
+    let mut is_positive = vec![false; csr.size()];
+    positives.iter().for_each(|&p| is_positive[p] = true);
+
+    let aurocs: Vec<f64> = (0..folds)
+        .map(|f| {
+            let mut train = vec![];
+            let mut is_test = vec![false; csr.size()];
+            for (i, &p) in shuffled.iter().enumerate() {
+                if i % folds == f {
+                    is_test[p] = true;
+                } else {
+                    train.push(p);
+                }
+            }
+
+            let score = neighbor_voting(csr, &train);
+            let (scores, labels): (Vec<f64>, Vec<bool>) = (0..csr.size())
+                .filter(|&v| is_test[v] || !is_positive[v])
+                .map(|v| (score[v], is_test[v]))
+                .unzip();
+            math::auroc(&scores, &labels)
+        })
+        .filter(|x| !x.is_nan())
+        .collect();
+
+    if aurocs.is_empty() {
+        f64::NAN
+    } else {
+        math::mean(&aurocs)
+    }
+}
+
+/// AUROC of ranking by node strength alone, the "node degree" baseline of EGAD. A term
+/// whose AUROC is close to this one is predicted from hubs rather than from its neighbors.
+pub fn degree_auroc(csr: &Csr, positives: &[usize]) -> f64 {
+    let mut labels = vec![false; csr.size()];
+    positives.iter().for_each(|&p| labels[p] = true);
+    let strength: Vec<f64> = (0..csr.size()).map(|v| csr.strength(v)).collect();
+    math::auroc(&strength, &labels)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::*;
+
+    // two 4-cliques joined by the edge 3 - 4
+    fn cliques() -> Csr {
+        let mut pairs = vec![(3, 4)];
+        for offset in [0, 4].iter() {
+            for i in 0..4 {
+                for j in i + 1..4 {
+                    pairs.push((offset + i, offset + j));
+                }
+            }
+        }
+        Csr::from_pairs(8, &pairs)
+    }
+
+    #[test]
+    fn test_neighbor_voting_1() {
+        let score = neighbor_voting(&cliques(), &[0, 1]);
+        assert_abs_diff_eq!(score[2], 2. / 3.);
+        assert_abs_diff_eq!(score[3], 2. / 4.);
+        assert_abs_diff_eq!(score[5], 0.);
+    }
+
+    #[test]
+    fn test_cross_validate_1() {
+        let csr = cliques();
+        assert_abs_diff_eq!(cross_validate(&csr, &[0, 1, 2, 3], 2, 0), 1.);
+        assert!(cross_validate(&csr, &[0, 5], 2, 0) < 0.5);
+    }
+}
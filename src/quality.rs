@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use rayon::prelude::*;
+
+use crate::graph::Csr;
+
+/// Quality of one cluster in a network. Edges from a cluster to genes without a cluster
+/// count as boundary edges.
+#[derive(Debug, Clone, Default)]
+pub struct ClusterQuality {
+    pub size: usize,
+    pub internal_edges: usize,
+    pub boundary_edges: usize,
+    /// internal edges / possible internal edges
+    pub density: f64,
+    /// weight of boundary edges / min(volume inside, volume outside)
+    pub conductance: f64,
+    /// contribution of the cluster to the modularity of the whole network
+    pub modularity: f64,
+    pub mean_rank_within: f64,
+    pub mean_rank_across: f64,
+    pub silhouette: f64,
+}
+
+fn mean(sum: f64, count: usize) -> f64 {
+    if count == 0 {
+        f64::NAN
+    } else {
+        sum / count as f64
+    }
+}
+
+/// Per cluster quality of `labels` (cluster `0..k` or `None`) on a network.
+///
+/// `weight`, `corr` and `rank` are CSR views of the same graph, so their neighbor lists
+/// are in the same order. Conductance and modularity use `weight`.
+pub fn cluster_quality(
+    weight: &Csr,
+    corr: &Csr,
+    rank: &Csr,
+    labels: &[Option<usize>],
+    k: usize,
+) -> Vec<ClusterQuality> {
+    let mut res = vec![ClusterQuality::default(); k];
+    let mut internal_w = vec![0.; k];
+    let mut cut_w = vec![0.; k];
+    let mut volume = vec![0.; k];
+    let mut rank_within = vec![0.; k];
+    let mut rank_across = vec![0.; k];
+    let total: f64 = (0..weight.size()).map(|v| weight.strength(v)).sum();
+
+    for (v, c) in labels.iter().enumerate() {
+        let c = match c {
+            Some(c) => *c,
+            None => continue,
+        };
+        res[c].size += 1;
+        volume[c] += weight.strength(v);
+        for ((&u, w), r) in weight
+            .neighbors(v)
+            .iter()
+            .zip(weight.weights(v))
+            .zip(rank.weights(v))
+        {
+            if labels[u] == Some(c) {
+                // every internal edge is visited from both ends
+                res[c].internal_edges += 1;
+                internal_w[c] += w;
+                rank_within[c] += r;
+            } else {
+                res[c].boundary_edges += 1;
+                cut_w[c] += w;
+                rank_across[c] += r;
+            }
+        }
+    }
+
+    let silhouette = silhouette(corr, labels, k);
+    let mut silhouette_sum = vec![0.; k];
+    for (s, c) in silhouette.iter().zip(labels) {
+        if let (Some(s), Some(c)) = (s, c) {
+            silhouette_sum[*c] += s;
+        }
+    }
+
+    for (c, q) in res.iter_mut().enumerate() {
+        q.mean_rank_within = mean(rank_within[c], q.internal_edges);
+        q.mean_rank_across = mean(rank_across[c], q.boundary_edges);
+        q.internal_edges /= 2;
+        q.density = if q.size > 1 {
+            2. * q.internal_edges as f64 / (q.size * (q.size - 1)) as f64
+        } else {
+            0.
+        };
+        let denominator = volume[c].min(total - volume[c]);
+        q.conductance = if denominator > 0. {
+            cut_w[c] / denominator
+        } else {
+            0.
+        };
+        if total > 0. {
+            q.modularity = internal_w[c] / total - (volume[c] / total).powi(2);
+        }
+        q.silhouette = mean(silhouette_sum[c], q.size);
+    }
+    res
+}
+
+/// Silhouette of each clustered node on the distance 1 - |corr|.
+///
+/// Gene pairs without an edge are taken to be uncorrelated (distance 1), so only the edges
+/// of the network are needed. Nodes in singleton clusters get 0.
+pub fn silhouette(corr: &Csr, labels: &[Option<usize>], k: usize) -> Vec<Option<f64>> {
+    let mut sizes = vec![0usize; k];
+    for c in labels.iter().flatten() {
+        sizes[*c] += 1;
+    }
+    let n_clusters = sizes.iter().filter(|&&s| s > 0).count();
+
+    (0..corr.size())
+        .into_par_iter()
+        .map(|v| {
+            let c = labels[v]?;
+            if sizes[c] == 1 || n_clusters < 2 {
+                return Some(0.);
+            }
+            let mut sum: HashMap<usize, f64> = HashMap::new();
+            for (&u, r) in corr.neighbors(v).iter().zip(corr.weights(v)) {
+                if let Some(cu) = labels[u] {
+                    *sum.entry(cu).or_insert(0.) += r.abs();
+                }
+            }
+
+            let a = 1. - sum.get(&c).cloned().unwrap_or(0.) / (sizes[c] - 1) as f64;
+            let adjacent = sum.keys().filter(|&&x| x != c).count();
+            // a non-adjacent cluster is at distance 1
+            let mut b = if adjacent < n_clusters - 1 {
+                1.
+            } else {
+                f64::INFINITY
+            };
+            for (&x, s) in sum.iter().filter(|(&x, _)| x != c) {
+                b = f64::min(b, 1. - s / sizes[x] as f64);
+            }
+
+            let max = a.max(b);
+            Some(if max > 0. { (b - a) / max } else { 0. })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::*;
+
+    #[test]
+    fn test_cluster_quality_1() {
+        // two triangles joined by the edge 2 - 3, node 6 is not clustered
+        let pairs = vec![
+            (0, 1),
+            (0, 2),
+            (1, 2),
+            (2, 3),
+            (3, 4),
+            (3, 5),
+            (4, 5),
+            (5, 6),
+        ];
+        let weight = Csr::from_pairs(7, &pairs);
+        let ranks: Vec<(usize, usize, f64)> = pairs
+            .iter()
+            .map(|&(a, b)| (a, b, if a / 3 == b / 3 { 1. } else { 10. }))
+            .collect();
+        let rank = Csr::from_weighted_pairs(7, &ranks);
+        let labels = vec![Some(0), Some(0), Some(0), Some(1), Some(1), Some(1), None];
+
+        let corrs: Vec<(usize, usize, f64)> = pairs
+            .iter()
+            .map(|&(a, b)| (a, b, if a / 3 == b / 3 { 0.5 } else { -0.9 }))
+            .collect();
+        let corr = Csr::from_weighted_pairs(7, &corrs);
+
+        let q = cluster_quality(&weight, &corr, &rank, &labels, 2);
+        assert_eq!(
+            (q[0].size, q[0].internal_edges, q[0].boundary_edges),
+            (3, 3, 1)
+        );
+        assert_eq!((q[1].internal_edges, q[1].boundary_edges), (3, 2));
+        assert_abs_diff_eq!(q[0].density, 1.);
+        // cut 1, volume 7 of 16
+        assert_abs_diff_eq!(q[0].conductance, 1. / 7.);
+        assert_abs_diff_eq!(q[0].modularity, 6. / 16. - (7f64 / 16.).powi(2));
+        assert_abs_diff_eq!(q[0].mean_rank_within, 1.);
+        assert_abs_diff_eq!(q[0].mean_rank_across, 10.);
+
+        // node 0: a = 1 - 1 / 2, b = 1
+        let s = silhouette(&corr, &labels, 2);
+        assert_abs_diff_eq!(s[0].unwrap(), 0.5);
+        // node 2: a = 0.5, b = 1 - 0.9 / 3
+        assert_abs_diff_eq!(s[2].unwrap(), 2. / 7.);
+        assert_eq!(s[6], None);
+    }
+}
@@ -3,8 +3,27 @@ use itertools::Itertools;
 use ndarray::{parallel::prelude::*, Array2, ArrayBase, Axis};
 use num_traits::Float;
 use ordered_float::OrderedFloat;
+use structopt::clap::arg_enum;
 use superslice::*;
 
+arg_enum! {
+    #[derive(Debug)]
+    pub enum Weight {
+        Unit,
+        Corr,
+        Inverse,
+        Decay,
+    }
+}
+
+arg_enum! {
+    #[derive(Debug)]
+    pub enum Rank {
+        HRR,
+        MR,
+    }
+}
+
 pub fn hrr<T: Ord>(a: T, b: T) -> T {
     std::cmp::max(a, b)
 }
@@ -13,6 +32,29 @@ pub fn mr<T: Float>(a: T, b: T) -> T {
     (a * b).sqrt()
 }
 
+/// HRR or MR of the gene pair `(i, j)` from a rank matrix.
+pub fn pair_rank(rank_arr: &Array2<usize>, method: &Rank, i: usize, j: usize) -> f64 {
+    match method {
+        Rank::HRR => hrr(rank_arr[[i, j]], rank_arr[[j, i]]) as f64,
+        Rank::MR => mr(rank_arr[[i, j]] as f64, rank_arr[[j, i]] as f64),
+    }
+}
+
+/// Convert an edge into a similarity weight.
+///
+/// * `Unit`: 1 for every edge
+/// * `Corr`: |corr|
+/// * `Inverse`: 1 / rank
+/// * `Decay`: exp(-(rank - 1) / decay), the exponential decay used for MR networks
+pub fn to_weight(weight: &Weight, corr: f64, rank: f64, decay: f64) -> f64 {
+    match weight {
+        Weight::Unit => 1.,
+        Weight::Corr => corr.abs(),
+        Weight::Inverse => 1. / rank.max(1.),
+        Weight::Decay => (-(rank - 1.).max(0.) / decay).exp(),
+    }
+}
+
 #[allow(dead_code)]
 pub fn construct_rank_matrix(corr: &Array2<f64>, size: usize) -> Result<Array2<usize>> {
     let mut rank_vec = vec![];
@@ -59,22 +101,18 @@ pub fn construct_rank_matrix_multithreading(
     Ok(ArrayBase::from_shape_vec((size, size), rank_vec)?)
 }
 
-pub fn get_index_sorted_by_rank(
-    rank_matrix: &Array2<usize>,
-    i: usize,
-    index: &[String],
-) -> Vec<String> {
-    let mut rank_vec: Vec<String> = vec!["".to_string(); index.len() - 1];
-
-    for j in 0..index.len() {
-        let rank = rank_matrix[[i, j]];
-        if rank == 0 {
-            continue;
-        }
-        rank_vec[rank - 1] = index[j].clone();
-    }
-
-    rank_vec
+/// Indices of the `k` elements ranked highest for row `i` (itself excluded), best first.
+/// Ties in rank are broken by index.
+pub fn top_k_by_rank(rank_matrix: &Array2<usize>, i: usize, k: usize) -> Vec<usize> {
+    let mut ranked: Vec<(usize, usize)> = rank_matrix
+        .row(i)
+        .iter()
+        .enumerate()
+        .filter(|&(j, _)| j != i)
+        .map(|(j, &rank)| (rank, j))
+        .collect();
+    ranked.sort_unstable();
+    ranked.into_iter().take(k).map(|x| x.1).collect()
 }
 
 #[cfg(test)]
@@ -95,6 +133,15 @@ mod test {
         assert_eq!(mr(1., 2.), (1.0 * 2.0).sqrt());
     }
 
+    #[test]
+    fn test_to_weight() {
+        assert_eq!(to_weight(&Weight::Unit, -0.5, 3., 5.), 1.);
+        assert_eq!(to_weight(&Weight::Corr, -0.5, 3., 5.), 0.5);
+        assert_eq!(to_weight(&Weight::Inverse, -0.5, 4., 5.), 0.25);
+        assert_eq!(to_weight(&Weight::Decay, 0.5, 1., 5.), 1.);
+        assert_eq!(to_weight(&Weight::Decay, 0.5, 11., 5.), (-2f64).exp());
+    }
+
     #[test]
     fn test_construct_rank_matrix_1() {
         let arr2 = array![[1.0, 0.9, 0.3], [0.9, 1.0, 0.5], [0.3, 0.5, 1.0]];
@@ -109,35 +156,33 @@ mod test {
     }
 
     #[test]
-    fn test_get_index_sorted_by_rank_1() {
+    fn test_top_k_by_rank_1() {
+        let rank: Array2<usize> = array![[0, 2, 1], [1, 0, 2], [2, 1, 0]];
+        assert_eq!(top_k_by_rank(&rank, 0, 2), [2, 1]);
+        assert_eq!(top_k_by_rank(&rank, 1, 1), [0]);
+
+        // tied ranks leave no gap
+        let rank: Array2<usize> = array![[0, 1, 1], [1, 0, 2], [2, 1, 0]];
+        assert_eq!(top_k_by_rank(&rank, 0, 2), [1, 2]);
+    }
+
+    #[test]
+    fn test_top_k_by_rank_2() {
         let rank: Array2<usize> = array![[0, 1, 2], [1, 0, 2], [2, 1, 0]];
 
         let index: Vec<String> = ["gene_1", "gene_2", "gene_3"]
             .iter()
             .map(|x| x.to_string())
             .collect();
-
-        assert_eq!(
-            get_index_sorted_by_rank(&rank, 0, &index),
-            ["gene_2", "gene_3"]
-                .iter()
-                .map(|x| x.to_string())
-                .collect::<Vec<String>>()
-        );
-
-        assert_eq!(
-            get_index_sorted_by_rank(&rank, 1, &index),
-            ["gene_1", "gene_3"]
-                .iter()
-                .map(|x| x.to_string())
-                .collect::<Vec<String>>()
-        );
-        assert_eq!(
-            get_index_sorted_by_rank(&rank, 2, &index),
-            ["gene_2", "gene_1"]
-                .iter()
-                .map(|x| x.to_string())
-                .collect::<Vec<String>>()
-        );
+        let top_names = |i: usize| -> Vec<String> {
+            top_k_by_rank(&rank, i, index.len() - 1)
+                .into_iter()
+                .map(|j| index[j].clone())
+                .collect()
+        };
+
+        assert_eq!(top_names(0), ["gene_2", "gene_3"]);
+        assert_eq!(top_names(1), ["gene_1", "gene_3"]);
+        assert_eq!(top_names(2), ["gene_2", "gene_1"]);
     }
 }
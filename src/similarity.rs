@@ -1,37 +1,239 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
 
+use ordered_float::OrderedFloat;
+use rayon::prelude::*;
+use structopt::clap::arg_enum;
+
+arg_enum! {
+    #[derive(Debug)]
+    pub enum RankMetric {
+        COSMIX,
+        RBO,
+        Kendall,
+        Jaccard,
+        AverageOverlap,
+    }
+}
+
 /// Cosmix score:
 ///
 /// $$ COSMIX(list, ref_list, k) = \sum_{i=1}^{k} n(i, list, ref_list) / \sum_{i=1}^{k} i $$
 /// where n(i, list, ref_list) is the number of elements in the top i element in list
-/// with corresponding elements in the top i elements in ref_list
+/// with corresponding elements in the top i elements in ref_list.
+/// `list` may be shorter than k (e.g. neighbors in a rank cutoff network); missing elements
+/// match nothing.
 pub fn cosmix<T: Hash + Eq>(list: &[T], ref_list: &[T], k: usize) -> f64 {
-    // k should smaller than list.len() or equal
-    assert!(k <= list.len());
-    // k should also smaller than ref_list.len() or equal
+    // k should smaller than ref_list.len() or equal
     assert!(k <= ref_list.len());
 
     let denominator: f64 = (1..=k).sum::<usize>() as f64;
     let mut numerator: f64 = 0.;
     let mut set = HashSet::new();
     let mut ref_set = HashSet::new();
-    for x in 0..k {
+    for (x, r) in ref_list.iter().take(k).enumerate() {
         // let set: HashSet<_> = list.iter().take(x).collect();
         // let ref_set: HashSet<_> = ref_list.iter().take(x).collect();
-        set.insert(&list[x]);
-        ref_set.insert(&ref_list[x]);
-        numerator += set.intersection(&ref_set).into_iter().count() as f64;
+        if let Some(v) = list.get(x) {
+            set.insert(v);
+        }
+        ref_set.insert(r);
+        numerator += set.intersection(&ref_set).count() as f64;
     }
 
     numerator / denominator
 }
 
+/// Neighbors of every node sorted by edge rank (stable, so ties keep input order).
+pub fn ranked_adjacency(size: usize, edges: &[(usize, usize, f64)]) -> Vec<Vec<usize>> {
+    let mut adj: Vec<Vec<(usize, OrderedFloat<f64>)>> = vec![vec![]; size];
+    for &(a, b, rank) in edges.iter() {
+        adj[a].push((b, OrderedFloat::from(rank)));
+        adj[b].push((a, OrderedFloat::from(rank)));
+    }
+    adj.into_par_iter()
+        .map(|mut neighbors| {
+            neighbors.sort_by_key(|x| x.1);
+            neighbors.into_iter().map(|x| x.0).collect()
+        })
+        .collect()
+}
+
+/// Number of elements shared by the top d elements of both lists, for d = 1..k.
+fn overlaps<T: Hash + Eq>(list: &[T], ref_list: &[T], k: usize) -> Vec<usize> {
+    let mut seen = HashSet::new();
+    let mut ref_seen = HashSet::new();
+    let mut overlap = 0;
+    (0..k)
+        .map(|d| {
+            let (v, r) = (list.get(d), ref_list.get(d));
+            if v.is_some() && v == r {
+                overlap += 1;
+            } else {
+                overlap += v.is_some_and(|v| ref_seen.contains(v)) as usize;
+                overlap += r.is_some_and(|r| seen.contains(r)) as usize;
+            }
+            seen.extend(v);
+            ref_seen.extend(r);
+            overlap
+        })
+        .collect()
+}
+
+/// Average overlap: the mean over depths d = 1..k of the fraction of the top d elements
+/// shared by both lists.
+pub fn average_overlap<T: Hash + Eq>(list: &[T], ref_list: &[T], k: usize) -> f64 {
+    if k == 0 {
+        return f64::NAN;
+    }
+    let sum: f64 = overlaps(list, ref_list, k)
+        .iter()
+        .enumerate()
+        .map(|(d, &x)| x as f64 / (d + 1) as f64)
+        .sum();
+    sum / k as f64
+}
+
+/// Extrapolated rank-biased overlap (Webber et al. 2010) of the top k elements, with
+/// persistence `p` in (0, 1): smaller `p` weights the top of the lists more.
+pub fn rbo<T: Hash + Eq>(list: &[T], ref_list: &[T], k: usize, p: f64) -> f64 {
+    if k == 0 {
+        return f64::NAN;
+    }
+    let overlaps = overlaps(list, ref_list, k);
+    let sum: f64 = overlaps
+        .iter()
+        .enumerate()
+        .map(|(d, &x)| x as f64 / (d + 1) as f64 * p.powi(d as i32 + 1))
+        .sum();
+    overlaps[k - 1] as f64 / k as f64 * p.powi(k as i32) + (1. - p) / p * sum
+}
+
+/// Jaccard index of the sets of the top k elements.
+pub fn top_k_jaccard<T: Hash + Eq>(list: &[T], ref_list: &[T], k: usize) -> f64 {
+    let (a, b) = (list.len().min(k), ref_list.len().min(k));
+    if a + b == 0 {
+        return f64::NAN;
+    }
+    let overlap = overlaps(list, ref_list, k).last().cloned().unwrap_or(0);
+    overlap as f64 / (a + b - overlap) as f64
+}
+
+/// Normalised Kendall tau distance between top k lists, K^(p) of Fagin et al. (2003):
+/// pairs ordered differently count 1, pairs whose order is unknown because both elements
+/// are missing from one list count `p`. 0 for identical and 1 for disjoint lists.
+pub fn kendall_tau_distance<T: Hash + Eq>(list: &[T], ref_list: &[T], k: usize, p: f64) -> f64 {
+    let a = &list[..list.len().min(k)];
+    let b = &ref_list[..ref_list.len().min(k)];
+    fn position<T: Hash + Eq>(x: &[T]) -> HashMap<&T, usize> {
+        let mut map = HashMap::new();
+        for (i, v) in x.iter().enumerate() {
+            map.entry(v).or_insert(i);
+        }
+        map
+    }
+    let (pos_a, pos_b) = (position(a), position(b));
+    let in_b: Vec<Option<usize>> = a.iter().map(|v| pos_b.get(v).cloned()).collect();
+    let in_a: Vec<Option<usize>> = b.iter().map(|v| pos_a.get(v).cloned()).collect();
+
+    let mut distance = 0.;
+    // pairs of elements of the first list: x ranked before y
+    for (i, x) in in_b.iter().enumerate() {
+        for y in in_b[i + 1..].iter() {
+            distance += match (x, y) {
+                (Some(x), Some(y)) if x > y => 1.,
+                (None, Some(_)) => 1.,
+                (None, None) => p,
+                _ => 0.,
+            };
+        }
+    }
+    // pairs of the second list with an element missing from the first
+    for (i, x) in in_a.iter().enumerate() {
+        for y in in_a[i + 1..].iter() {
+            distance += match (x, y) {
+                (None, Some(_)) => 1.,
+                (None, None) => p,
+                _ => 0.,
+            };
+        }
+    }
+    // elements of one list only against elements of the other list only
+    let only_a = in_b.iter().filter(|x| x.is_none()).count();
+    let only_b = in_a.iter().filter(|x| x.is_none()).count();
+    distance += (only_a * only_b) as f64;
+
+    let pairs = |n: usize| (n * n.saturating_sub(1) / 2) as f64;
+    let max = (a.len() * b.len()) as f64 + p * (pairs(a.len()) + pairs(b.len()));
+    if max == 0. {
+        0.
+    } else {
+        distance / max
+    }
+}
+
+/// Similarity of `list` to `ref_list` over the top k with `metric`, from 0 to 1 (identical
+/// lists). Kendall tau is turned into a similarity as 1 - distance with p = 0.5; `rbo_p` is
+/// the persistence of RBO.
+pub fn list_similarity<T: Hash + Eq>(
+    metric: &RankMetric,
+    list: &[T],
+    ref_list: &[T],
+    k: usize,
+    rbo_p: f64,
+) -> f64 {
+    match metric {
+        RankMetric::COSMIX => cosmix(list, ref_list, k),
+        RankMetric::RBO => rbo(list, ref_list, k, rbo_p),
+        RankMetric::Kendall => 1. - kendall_tau_distance(list, ref_list, k, 0.5),
+        RankMetric::Jaccard => top_k_jaccard(list, ref_list, k),
+        RankMetric::AverageOverlap => average_overlap(list, ref_list, k),
+    }
+}
+
+/// Score of every node: its neighbors (by `adj`, best first) against the reference top list
+/// of its label with `metric`; `None` for unlabelled nodes and nodes without neighbors.
+/// `labels` maps nodes to rows of `reference`, so shuffling `labels` gives a permutation
+/// null model.
+pub fn neighbor_scores(
+    adj: &[Vec<usize>],
+    labels: &[Option<usize>],
+    reference: &[Vec<usize>],
+    k: usize,
+    metric: &RankMetric,
+    rbo_p: f64,
+) -> Vec<Option<f64>> {
+    (0..adj.len())
+        .into_par_iter()
+        .map(|i| {
+            let label = labels[i]?;
+            if adj[i].is_empty() {
+                return None;
+            }
+            let list: Vec<Option<usize>> = adj[i].iter().map(|&j| labels[j]).collect();
+            let ref_list: Vec<Option<usize>> = reference[label].iter().map(|&x| Some(x)).collect();
+            Some(list_similarity(metric, &list, &ref_list, k, rbo_p))
+        })
+        .collect()
+}
+
+/// COSMIX of every node, as `neighbor_scores`.
+pub fn cosmix_scores(
+    adj: &[Vec<usize>],
+    labels: &[Option<usize>],
+    reference: &[Vec<usize>],
+    k: usize,
+) -> Vec<Option<f64>> {
+    neighbor_scores(adj, labels, reference, k, &RankMetric::COSMIX, 0.)
+}
+
 #[cfg(test)]
 mod test {
     use std::vec;
 
-    use super::cosmix;
+    use super::*;
+    use approx::*;
+    use proptest::prelude::*;
 
     #[test]
     fn test_cosmix_1() {
@@ -52,4 +254,103 @@ mod test {
         assert_eq!(cosmix(&l, &rl, 2), 3. / 3.);
         assert_eq!(cosmix(&l, &rl, 3), 6. / 6.);
     }
+
+    #[test]
+    fn test_cosmix_3() {
+        let l = vec![1, 3];
+        let rl = vec![1, 2, 3, 4, 5];
+
+        // 1 + 1 + 2
+        assert_eq!(cosmix(&l, &rl, 3), 4. / 6.);
+    }
+
+    #[test]
+    fn test_cosmix_scores_1() {
+        // 0 - 1 (rank 2), 0 - 2 (rank 1)
+        let adj = ranked_adjacency(3, &[(0, 1, 2.), (0, 2, 1.)]);
+        assert_eq!(adj[0], [2, 1]);
+
+        let reference = vec![vec![2, 1], vec![0, 2], vec![0, 1]];
+        let scores = cosmix_scores(&adj, &[Some(0), Some(1), Some(2)], &reference, 2);
+        // node 0 matches fully; node 1 has only neighbor 0: (1 + 1) / 3
+        assert_eq!(scores, [Some(1.), Some(2. / 3.), Some(2. / 3.)]);
+
+        // unlabelled nodes are skipped and match nothing
+        let scores = cosmix_scores(&adj, &[Some(0), Some(1), None], &reference, 2);
+        assert_eq!(scores, [Some(1. / 3.), Some(2. / 3.), None]);
+    }
+
+    #[test]
+    fn test_rank_metrics_1() {
+        let (a, b) = (vec![1, 2, 3], vec![1, 3, 2]);
+        assert_abs_diff_eq!(average_overlap(&a, &b, 3), (1. + 0.5 + 1.) / 3.);
+        assert_abs_diff_eq!(top_k_jaccard(&[1, 2, 3], &[2, 3, 4], 3), 0.5);
+        // reversed: 3 discordant pairs of at most 3 * 3 + 0.5 * (3 + 3)
+        assert_abs_diff_eq!(kendall_tau_distance(&[1, 2, 3], &[3, 2, 1], 3, 0.5), 0.25);
+        // 1 only in the first list, 4 only in the second, 2 and 3 concordant
+        assert_abs_diff_eq!(
+            kendall_tau_distance(&[1, 2, 3], &[2, 3, 4], 3, 0.),
+            // (1, 4): 1; (1, 2), (1, 3): 2 before 1 in the second list; (4, 2), (4, 3): 0
+            3. / 9.
+        );
+        assert_abs_diff_eq!(rbo(&a, &a, 3, 0.9), 1., epsilon = 1e-12);
+        // short lists only match what they have
+        assert_abs_diff_eq!(top_k_jaccard(&[1], &[1, 2, 3], 3), 1. / 3.);
+    }
+
+    fn metrics(a: &[usize], b: &[usize], k: usize) -> Vec<f64> {
+        vec![
+            cosmix(a, b, k),
+            rbo(a, b, k, 0.9),
+            1. - kendall_tau_distance(a, b, k, 0.5),
+            top_k_jaccard(a, b, k),
+            average_overlap(a, b, k),
+        ]
+    }
+
+    fn unique_list() -> impl Strategy<Value = Vec<usize>> {
+        prop::collection::hash_set(0usize..1000, 2..30).prop_map(|x| x.into_iter().collect())
+    }
+
+    proptest! {
+        #[test]
+        fn prop_identical_lists(a in unique_list()) {
+            for m in metrics(&a, &a, a.len()) {
+                prop_assert!((m - 1.).abs() < 1e-9);
+            }
+        }
+
+        #[test]
+        fn prop_disjoint_lists(a in unique_list()) {
+            let b: Vec<usize> = a.iter().map(|x| x + 1000).collect();
+            for m in metrics(&a, &b, a.len()) {
+                prop_assert!(m.abs() < 1e-9);
+            }
+        }
+
+        #[test]
+        fn prop_partial_overlap(a in unique_list(), replace in any::<u64>(), rotate in 0usize..30) {
+            // the first element is replaced and the last is kept, the rest at random
+            let n = a.len();
+            let mut b: Vec<usize> = a
+                .iter()
+                .enumerate()
+                .map(|(i, &x)| {
+                    if i == 0 || (i + 1 < n && replace >> (i % 64) & 1 == 1) {
+                        x + 1000
+                    } else {
+                        x
+                    }
+                })
+                .collect();
+            b.rotate_left(rotate % n);
+            for m in metrics(&a, &b, n) {
+                prop_assert!(m > 0. && m < 1.);
+            }
+            // all but COSMIX are symmetric
+            for (x, y) in metrics(&a, &b, n).iter().zip(metrics(&b, &a, n)).skip(1) {
+                prop_assert!((x - y).abs() < 1e-9);
+            }
+        }
+    }
 }
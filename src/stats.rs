@@ -0,0 +1,143 @@
+use std::collections::{BTreeMap, HashSet};
+
+use rayon::prelude::*;
+
+use crate::graph::Csr;
+use crate::math;
+
+/// Number of nodes for each degree, in ascending order of degree.
+pub fn degree_distribution(csr: &Csr) -> Vec<(usize, usize)> {
+    let mut map: BTreeMap<usize, usize> = BTreeMap::new();
+    for i in 0..csr.size() {
+        *map.entry(csr.degree(i)).or_default() += 1;
+    }
+    map.into_iter().collect()
+}
+
+/// Scale-free topology fit index as in WGCNA's `scaleFreeFitIndex`.
+///
+/// Connectivity is cut into `n_bins` equal-width bins and log10(p(k)) is regressed on
+/// log10(mean k in the bin). Returns `(r2, slope)`; a scale-free network has a high r2
+/// with a negative slope. Both are NaN with fewer than 2 bins.
+pub fn scale_free_fit(connectivity: &[f64], n_bins: usize) -> (f64, f64) {
+    assert!(!connectivity.is_empty());
+    if n_bins < 2 {
+        return (f64::NAN, f64::NAN);
+    }
+    let min = connectivity.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = connectivity
+        .iter()
+        .cloned()
+        .fold(f64::NEG_INFINITY, f64::max);
+    let width = (max - min) / n_bins as f64;
+    if width == 0. {
+        return (f64::NAN, f64::NAN);
+    }
+
+    let mut sum = vec![0.; n_bins];
+    let mut count = vec![0usize; n_bins];
+    for &k in connectivity {
+        let b = std::cmp::min(((k - min) / width) as usize, n_bins - 1);
+        sum[b] += k;
+        count[b] += 1;
+    }
+
+    let mut log_k = vec![];
+    let mut log_p = vec![];
+    for b in 0..n_bins {
+        let mean_k = if count[b] == 0 {
+            min + width * (b as f64 + 0.5)
+        } else {
+            sum[b] / count[b] as f64
+        };
+        if mean_k <= 0. {
+            continue;
+        }
+        let p = count[b] as f64 / connectivity.len() as f64;
+        log_k.push(mean_k.log10());
+        log_p.push((p + 1e-9).log10());
+    }
+
+    if log_k.len() < 2 {
+        return (f64::NAN, f64::NAN);
+    }
+    let (slope, _, r2) = math::linear_regression(&log_k, &log_p);
+    (r2, slope)
+}
+
+/// Local clustering coefficient of each node. Nodes with degree < 2 have 0.
+pub fn local_clustering(csr: &Csr) -> Vec<f64> {
+    (0..csr.size())
+        .into_par_iter()
+        .map(|v| {
+            let d = csr.degree(v);
+            if d < 2 {
+                return 0.;
+            }
+            let neighbors: HashSet<usize> = csr.neighbors(v).iter().cloned().collect();
+            let links: usize = csr
+                .neighbors(v)
+                .iter()
+                .map(|&u| {
+                    csr.neighbors(u)
+                        .iter()
+                        .filter(|w| neighbors.contains(w))
+                        .count()
+                })
+                .sum();
+            // every link between two neighbors is counted from both ends
+            links as f64 / (d * (d - 1)) as f64
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::{Edge, Graph};
+    use approx::*;
+
+    fn triangle_with_tail() -> Csr {
+        let nodes: Vec<String> = (0..4).map(|x| x.to_string()).collect();
+        let edges = vec![
+            Edge::new(0, 1, 0.9, 1.),
+            Edge::new(1, 2, 0.8, 1.),
+            Edge::new(0, 2, 0.8, 2.),
+            Edge::new(2, 3, 0.7, 3.),
+        ];
+        Graph::from_edges(&nodes, &edges).to_csr()
+    }
+
+    #[test]
+    fn test_degree_distribution_1() {
+        let csr = triangle_with_tail();
+        assert_eq!(degree_distribution(&csr), vec![(1, 1), (2, 2), (3, 1)]);
+    }
+
+    #[test]
+    fn test_local_clustering_1() {
+        let csr = triangle_with_tail();
+        let cc = local_clustering(&csr);
+        assert_abs_diff_eq!(cc[0], 1.);
+        assert_abs_diff_eq!(cc[1], 1.);
+        assert_abs_diff_eq!(cc[2], 1. / 3.);
+        assert_abs_diff_eq!(cc[3], 0.);
+    }
+
+    #[test]
+    fn test_scale_free_fit_1() {
+        // p(k) ~ k^-2
+        let mut k = vec![];
+        for (degree, n) in [(1., 144), (2., 36), (3., 16), (4., 9)] {
+            k.extend(std::iter::repeat_n(degree, n));
+        }
+        let (r2, slope) = scale_free_fit(&k, 4);
+        assert_abs_diff_eq!(slope, -2., epsilon = 1e-6);
+        assert_abs_diff_eq!(r2, 1., epsilon = 1e-6);
+
+        for n_bins in [0, 1] {
+            let (r2, slope) = scale_free_fit(&k, n_bins);
+            assert!(r2.is_nan() && slope.is_nan());
+        }
+    }
+}
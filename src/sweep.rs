@@ -0,0 +1,106 @@
+use ndarray::{Array2, Axis};
+use ordered_float::OrderedFloat;
+use rayon::prelude::*;
+
+use crate::graph::RankedEdge;
+use crate::rank::{self, Rank};
+use crate::similarity;
+
+/// Replace each row by the average ranks of its values, so that Pearson correlation of the
+/// result is Spearman correlation of the input.
+pub fn rank_transform_rows(arr: &mut Array2<f64>) {
+    arr.axis_iter_mut(Axis(0))
+        .into_par_iter()
+        .for_each(|mut row| {
+            let mut order: Vec<usize> = (0..row.len()).collect();
+            order.sort_by_key(|&i| OrderedFloat::from(row[i]));
+            let mut ranks = vec![0.; row.len()];
+            let mut i = 0;
+            while i < order.len() {
+                let mut j = i;
+                while j < order.len() && row[order[j]] == row[order[i]] {
+                    j += 1;
+                }
+                for &x in order[i..j].iter() {
+                    ranks[x] = (i + j + 1) as f64 / 2.;
+                }
+                i = j;
+            }
+            row.iter_mut().zip(ranks).for_each(|(x, r)| *x = r);
+        });
+}
+
+/// Every gene pair whose HRR or MR is at most `max_rank`, sorted by rank, so network
+/// variants with smaller cutoffs are filters of it.
+pub fn ranked_edges(
+    corr: &Array2<f64>,
+    rank_arr: &Array2<usize>,
+    method: &Rank,
+    max_rank: f64,
+) -> Vec<RankedEdge> {
+    let n = corr.nrows();
+    let mut edges: Vec<RankedEdge> = (0..n)
+        .into_par_iter()
+        .flat_map_iter(|i| {
+            (i + 1..n).filter_map(move |j| {
+                let r = rank::pair_rank(rank_arr, method, i, j);
+                if r <= max_rank {
+                    Some((i, j, corr[[i, j]], r))
+                } else {
+                    None
+                }
+            })
+        })
+        .collect();
+    edges.par_sort_unstable_by(|a, b| a.3.partial_cmp(&b.3).unwrap());
+    edges
+}
+
+/// Codon score (median COSMIX) of a network given as edges sorted by rank, like
+/// `codon-usage`. `to_fasta` maps genes of the network to sequences and `codon_top` holds
+/// the top `k` sequences by codon usage similarity of each sequence.
+pub fn codon_score(
+    edges: &[RankedEdge],
+    n_genes: usize,
+    to_fasta: &[Option<usize>],
+    codon_top: &[Vec<usize>],
+    k: usize,
+) -> f64 {
+    let edges: Vec<(usize, usize, f64)> = edges.iter().map(|e| (e.0, e.1, e.3)).collect();
+    let adj = similarity::ranked_adjacency(n_genes, &edges);
+    let values: Vec<f64> = similarity::cosmix_scores(&adj, to_fasta, codon_top, k)
+        .into_iter()
+        .flatten()
+        .collect();
+    if values.is_empty() {
+        f64::NAN
+    } else {
+        crate::math::median(&values)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_rank_transform_rows_1() {
+        let mut arr = array![[10., 30., 20., 20.]];
+        rank_transform_rows(&mut arr);
+        assert_eq!(arr, array![[1., 4., 2.5, 2.5]]);
+    }
+
+    #[test]
+    fn test_ranked_edges_1() {
+        let corr = array![[1.0, 0.9, 0.3], [0.9, 1.0, 0.5], [0.3, 0.5, 1.0]];
+        let rank_arr = rank::construct_rank_matrix_multithreading(&corr, 3).unwrap();
+
+        let hrr = ranked_edges(&corr, &rank_arr, &Rank::HRR, 1.);
+        assert_eq!(hrr, vec![(0, 1, 0.9, 1.)]);
+
+        let mr = ranked_edges(&corr, &rank_arr, &Rank::MR, 2.);
+        assert_eq!(mr.len(), 3);
+        assert_eq!(mr[2], (0, 2, 0.3, 2.));
+    }
+}
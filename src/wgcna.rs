@@ -0,0 +1,225 @@
+use ndarray::{Array1, Array2, ArrayView1, Axis};
+use rand::{rngs::StdRng, seq::index::sample, SeedableRng};
+use rayon::prelude::*;
+
+use crate::math;
+use crate::stats;
+
+/// Candidate soft-thresholding powers, as in WGCNA's `pickSoftThreshold` examples.
+pub const POWERS: [f64; 15] = [
+    1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 12., 14., 16., 18., 20.,
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SoftThreshold {
+    pub power: f64,
+    pub sft_r2: f64,
+    pub slope: f64,
+    pub signed_r2: f64,
+    pub mean_k: f64,
+    pub median_k: f64,
+    pub max_k: f64,
+}
+
+/// Center and scale each row to unit norm, so the dot product of two rows is their
+/// Pearson correlation. Rows without variance become zero.
+pub fn standardize(arr: &Array2<f64>) -> Array2<f64> {
+    let mut z = arr.to_owned();
+    for mut row in z.axis_iter_mut(Axis(0)) {
+        let mean = row.mean().unwrap_or(0.);
+        row.mapv_inplace(|x| x - mean);
+        let norm = row.dot(&row).sqrt();
+        if norm > 0. {
+            row.mapv_inplace(|x| x / norm);
+        }
+    }
+    z
+}
+
+/// Co-expression similarity of two standardized rows.
+pub fn similarity(a: ArrayView1<f64>, b: ArrayView1<f64>, signed: bool) -> f64 {
+    let c = a.dot(&b);
+    if signed {
+        (1. + c) / 2.
+    } else {
+        c.abs()
+    }
+}
+
+/// Scale-free fit of the soft-thresholded network for every power in `powers`.
+///
+/// Connectivity is accumulated one row of the correlation matrix at a time, so memory is
+/// O(n * powers) rather than O(n^2).
+pub fn soft_threshold_table(z: &Array2<f64>, powers: &[f64], signed: bool) -> Vec<SoftThreshold> {
+    let n = z.nrows();
+    // connectivity[i][p]
+    let connectivity: Vec<Vec<f64>> = (0..n)
+        .into_par_iter()
+        .map(|i| {
+            let mut k = vec![0.; powers.len()];
+            for j in 0..n {
+                if i == j {
+                    continue;
+                }
+                let s = similarity(z.row(i), z.row(j), signed);
+                for (kp, p) in k.iter_mut().zip(powers) {
+                    *kp += s.powf(*p);
+                }
+            }
+            k
+        })
+        .collect();
+
+    powers
+        .iter()
+        .enumerate()
+        .map(|(p, &power)| {
+            let k: Vec<f64> = connectivity.iter().map(|row| row[p]).collect();
+            let (sft_r2, slope) = stats::scale_free_fit(&k, 10);
+            SoftThreshold {
+                power,
+                sft_r2,
+                slope,
+                signed_r2: -slope.signum() * sft_r2,
+                mean_k: math::mean(&k),
+                median_k: math::median(&k),
+                max_k: k.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+            }
+        })
+        .collect()
+}
+
+/// The lowest power whose signed R^2 reaches `r2_cutoff`.
+pub fn pick_power(table: &[SoftThreshold], r2_cutoff: f64) -> Option<f64> {
+    table
+        .iter()
+        .find(|t| t.signed_r2 >= r2_cutoff)
+        .map(|t| t.power)
+}
+
+/// Topological overlap matrix of a block of standardized rows:
+/// TOM_ij = (l_ij + a_ij) / (min(k_i, k_j) + 1 - a_ij) with l = A^2 and a zero diagonal.
+pub fn tom(z: &Array2<f64>, power: f64, signed: bool) -> Array2<f64> {
+    let n = z.nrows();
+    let mut adj = z.dot(&z.t());
+    adj.mapv_inplace(|c| if signed { (1. + c) / 2. } else { c.abs() }.powf(power));
+    adj.diag_mut().fill(0.);
+
+    let k: Array1<f64> = adj.sum_axis(Axis(1));
+    let l = adj.dot(&adj);
+
+    Array2::from_shape_fn((n, n), |(i, j)| {
+        if i == j {
+            1.
+        } else {
+            (l[[i, j]] + adj[[i, j]]) / (k[i].min(k[j]) + 1. - adj[[i, j]])
+        }
+    })
+}
+
+/// Split rows into blocks of at most `max_block_size` by k-means on standardized rows
+/// (cosine similarity), like the projective pre-clustering of WGCNA's `blockwiseModules`.
+pub fn blocks(z: &Array2<f64>, max_block_size: usize, seed: u64) -> Vec<Vec<usize>> {
+    let n = z.nrows();
+    if n <= max_block_size {
+        return vec![(0..n).collect()];
+    }
+    let k = n.div_ceil(max_block_size);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut centers: Vec<Array1<f64>> = sample(&mut rng, n, k)
+        .into_iter()
+        .map(|i| z.row(i).to_owned())
+        .collect();
+
+    let mut assign = vec![0; n];
+    for _ in 0..20 {
+        let next: Vec<usize> = (0..n)
+            .into_par_iter()
+            .map(|i| {
+                (0..k)
+                    .max_by(|&a, &b| {
+                        let (sa, sb) = (z.row(i).dot(&centers[a]), z.row(i).dot(&centers[b]));
+                        sa.total_cmp(&sb)
+                    })
+                    .unwrap()
+            })
+            .collect();
+        if next == assign {
+            break;
+        }
+        assign = next;
+
+        for (c, center) in centers.iter_mut().enumerate() {
+            let mut sum = Array1::zeros(z.ncols());
+            for i in (0..n).filter(|&i| assign[i] == c) {
+                sum += &z.row(i);
+            }
+            let norm = sum.dot(&sum).sqrt();
+            if norm > 0. {
+                *center = sum / norm;
+            }
+        }
+    }
+
+    // pack clusters into blocks, splitting any cluster that is too large
+    let mut clusters: Vec<Vec<usize>> = vec![vec![]; k];
+    for (i, &c) in assign.iter().enumerate() {
+        clusters[c].push(i);
+    }
+    clusters.sort_by_key(|c| std::cmp::Reverse(c.len()));
+
+    let mut blocks: Vec<Vec<usize>> = vec![];
+    for cluster in clusters.into_iter().filter(|c| !c.is_empty()) {
+        for chunk in cluster.chunks(max_block_size) {
+            match blocks
+                .iter_mut()
+                .find(|b| b.len() + chunk.len() <= max_block_size)
+            {
+                Some(b) => b.extend_from_slice(chunk),
+                None => blocks.push(chunk.to_vec()),
+            }
+        }
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_standardize_1() {
+        let arr = array![[1., 2., 3., 4.], [2., 4., 6., 8.], [4., 3., 2., 1.]];
+        let z = standardize(&arr);
+        assert_abs_diff_eq!(similarity(z.row(0), z.row(1), false), 1., epsilon = 1e-12);
+        assert_abs_diff_eq!(similarity(z.row(0), z.row(2), false), 1., epsilon = 1e-12);
+        assert_abs_diff_eq!(similarity(z.row(0), z.row(2), true), 0., epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_tom_1() {
+        let arr = array![[1., 2., 3., 4.], [2., 4., 6., 8.], [1., 3., 2., 4.]];
+        let z = standardize(&arr);
+        let t = tom(&z, 1., false);
+
+        // a_01 = 1, a_02 = a_12 = 0.8
+        let (a01, a02, a12) = (1., 0.8, 0.8);
+        let expected = (a02 * a12 + a01) / (f64::min(a01 + a02, a01 + a12) + 1. - a01);
+        assert_abs_diff_eq!(t[[0, 1]], expected, epsilon = 1e-12);
+        assert_abs_diff_eq!(t[[0, 1]], t[[1, 0]], epsilon = 1e-12);
+        assert_eq!(t[[2, 2]], 1.);
+    }
+
+    #[test]
+    fn test_blocks_1() {
+        let arr = Array2::from_shape_fn((10, 4), |(i, j)| ((i * 7 + j * 3) % 5) as f64);
+        let z = standardize(&arr);
+        let b = blocks(&z, 4, 0);
+        assert!(b.iter().all(|x| x.len() <= 4));
+        let mut all: Vec<usize> = b.into_iter().flatten().collect();
+        all.sort_unstable();
+        assert_eq!(all, (0..10).collect::<Vec<usize>>());
+    }
+}